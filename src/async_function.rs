@@ -0,0 +1,73 @@
+use std::{collections::HashMap, future::Future, pin::Pin};
+
+use async_trait::async_trait;
+
+use crate::{
+    async_resolver::AsyncResolver,
+    error::Result,
+    function::{check_arguments, ArgumentType, Signatures, WithFunctions},
+    value::Value,
+};
+
+type AsyncFunction = Box<dyn Fn(Vec<Value>) -> Pin<Box<dyn Future<Output = Result<Value>> + Send>> + Send + Sync>;
+
+/// Async-version of [crate::function::FunctionRegistry] whose functions return a [Future]
+#[derive(Default)]
+pub struct AsyncFunctionRegistry {
+    functions: HashMap<String, (Vec<ArgumentType>, AsyncFunction)>,
+}
+
+impl AsyncFunctionRegistry {
+    /// Registers `function` as `name` taking arguments of the given `types`, replacing any function registered with
+    /// that name before. `function` is only called with arguments matching `types`.
+    pub fn register<F, Fut>(
+        &mut self,
+        name: impl Into<String>,
+        types: impl Into<Vec<ArgumentType>>,
+        function: F,
+    ) -> &mut Self
+    where
+        F: Fn(Vec<Value>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let function: AsyncFunction = Box::new(move |arguments| Box::pin(function(arguments)));
+        self.functions.insert(name.into(), (types.into(), function));
+        self
+    }
+
+    /// Calls the function `name` on `arguments` or is [None] if there is no such function
+    pub async fn call(&self, name: &str, arguments: &[Value]) -> Option<Result<Value>> {
+        let (types, function) = self.functions.get(name)?;
+        Some(match check_arguments(name, types, arguments) {
+            Ok(true) => function(arguments.to_vec()).await,
+            Ok(false) => Ok(Value::Null),
+            Err(error) => Err(error),
+        })
+    }
+}
+
+impl Signatures for AsyncFunctionRegistry {
+    fn arguments(&self, name: &str) -> Option<&[ArgumentType]> {
+        self.functions.get(name).map(|(types, _)| types.as_slice())
+    }
+}
+
+impl std::fmt::Debug for AsyncFunctionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.functions.iter().map(|(name, (types, _))| (name, types)))
+            .finish()
+    }
+}
+
+#[async_trait(?Send)]
+impl<R: AsyncResolver> AsyncResolver for WithFunctions<'_, R, AsyncFunctionRegistry> {
+    #[allow(mismatched_lifetime_syntaxes)]
+    async fn resolve<'a>(&self, name: impl AsRef<str> + Send + 'a) -> Option<&Value> {
+        self.resolver.resolve(name).await
+    }
+
+    async fn call(&self, name: &str, arguments: &[Value]) -> Option<Result<Value>> {
+        self.functions.call(name, arguments).await
+    }
+}
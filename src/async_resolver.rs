@@ -1,9 +1,16 @@
 use async_trait::async_trait;
 
-use crate::value::Value;
+use crate::{error::Result, value::Value};
 
-#[async_trait]
+#[async_trait(?Send)]
 pub trait AsyncResolver {
     /// Resolves `name` to a [Value] or [None] if there is no value for `name`
+    #[allow(mismatched_lifetime_syntaxes)]
     async fn resolve<'a>(&self, name: impl AsRef<str> + Send + 'a) -> Option<&Value>;
+
+    /// Calls the custom function `name` on `arguments` or is [None] if there is no such function, so the
+    /// [crate::function::Builtin] of that name is called instead. See [crate::async_function::AsyncFunctionRegistry].
+    async fn call(&self, _name: &str, _arguments: &[Value]) -> Option<Result<Value>> {
+        None
+    }
 }
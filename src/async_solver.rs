@@ -1,71 +1,175 @@
-use anyhow::{anyhow, Result};
 use async_recursion::async_recursion;
 
 use crate::{
+    async_function::AsyncFunctionRegistry,
     async_resolver::AsyncResolver,
-    calculate::{Calculateable, Calculation},
-    compare::{Compareable, Comparison, ComparisonType, Logic},
+    calculate::Calculateable,
+    compare::{Between, Comparison, ComparisonType, Logic},
+    error::{Error, Result},
+    explain::{Explain, Explanation},
+    function::WithFunctions,
+    record::{Record, RecordOperand, Solve, SolveTristate},
     sequence::{Entity, Sequence},
+    tristate::Tristate,
     value::Value,
 };
 
-async fn produce_final_value(
-    input_value: Value,
-    calculations: &Vec<Calculation>,
-    resolver: &impl AsyncResolver,
-) -> Result<Value> {
-    let mut init = input_value;
-    for item in calculations {
-        let (item_value, item_arithmetic) = match item {
-            Calculation::Value(value, arithmetic) => (Some(value), arithmetic),
-            Calculation::Variable(name, arithmetic) => (resolver.resolve(name).await, arithmetic),
-        };
+/// Solves `operand`, which is recorded as `O`. All solvers and [explain_tree] solve operands through this.
+#[async_recursion(?Send)]
+async fn record_var<O>(operand: &ComparisonType, resolver: &impl AsyncResolver) -> Result<O>
+where
+    O: RecordOperand,
+{
+    match operand {
+        ComparisonType::Value(value) => Ok(O::fixed(value.clone())),
+        ComparisonType::Variable(variable_name) => resolver
+            .resolve(variable_name)
+            .await
+            .map(|value| O::variable(variable_name, value.clone()))
+            .ok_or_else(|| Error::UnresolvedVariable(variable_name.clone())),
+        ComparisonType::Calculation(calculation) => {
+            let lhs = record_var::<O>(&calculation.lhs, resolver).await?;
+            let rhs = record_var::<O>(&calculation.rhs, resolver).await?;
+            let value = lhs.value().clone().calculate(rhs.value(), calculation.arithmetic)?;
+            Ok(O::calculation(lhs, calculation.arithmetic, rhs, value))
+        }
+        ComparisonType::Negative(negated) => {
+            let negated = record_var::<O>(negated, resolver).await?;
+            let value = (-negated.value().clone())?;
+            Ok(O::negative(negated, value))
+        }
+        ComparisonType::Coalesce(lhs, fallback) => match record_var::<O>(lhs, resolver).await {
+            Err(Error::UnresolvedVariable(_)) => Ok(O::coalesce(
+                operand,
+                record_var::<O>(fallback, resolver).await?,
+                Some(lhs),
+            )),
+            Ok(used) if used.value() == &Value::Null => Ok(O::coalesce(
+                operand,
+                record_var::<O>(fallback, resolver).await?,
+                Some(lhs),
+            )),
+            used => Ok(O::coalesce(operand, used?, None)),
+        },
+        ComparisonType::Exists(name) => Ok(O::exists(name, Value::Bool(resolver.resolve(name).await.is_some()))),
+        ComparisonType::Pattern(pattern) => Ok(O::fixed(pattern.value())),
+        ComparisonType::Call(call) => {
+            let mut arguments = Vec::with_capacity(call.arguments.len());
+            for argument in &call.arguments {
+                arguments.push(record_var::<O>(argument, resolver).await?);
+            }
+            let values: Vec<Value> = arguments.iter().map(|argument| argument.value().clone()).collect();
+            let value = match resolver.call(&call.name, &values).await {
+                Some(result) => result?,
+                None => call.builtin()?.call(values)?,
+            };
+            Ok(O::call(&call.name, arguments, value))
+        }
+    }
+}
 
-        let v = item_value.ok_or_else(|| anyhow!("Unable to resolve variables"))?;
+async fn record_one<R: Record>(comparison: &Comparison, resolver: &impl AsyncResolver) -> Result<R::Entity> {
+    let what = record_var::<R::Operand>(&comparison.what, resolver).await?;
+    let against = record_var::<R::Operand>(&comparison.against, resolver).await?;
+    let result = comparison.compare_values(what.value(), against.value())?;
 
-        init = init.calculate(v, *item_arithmetic)?;
-    }
+    Ok(R::comparison(what, comparison.operator, against, result))
+}
 
-    Ok(init)
+async fn record_between<R: Record>(between: &Between, resolver: &impl AsyncResolver) -> Result<R::Entity> {
+    let what = record_var::<R::Operand>(&between.what, resolver).await?;
+    let from = record_var::<R::Operand>(&between.from, resolver).await?;
+    let to = record_var::<R::Operand>(&between.to, resolver).await?;
+    let result = what.value().between(from.value(), to.value())?;
+
+    Ok(R::between(what, from, to, result))
 }
 
-async fn resolve_var(comparison: &ComparisonType, resolver: &impl AsyncResolver) -> Result<Value> {
-    let (value, calculations) = match comparison {
-        ComparisonType::Value(ref value, ref calculations) => (Some(value), calculations),
-        ComparisonType::Variable(ref variable_name, ref calculations) => {
-            (resolver.resolve(variable_name).await, calculations)
+async fn record_predicate<R: Record>(operand: &ComparisonType, resolver: &impl AsyncResolver) -> Result<R::Entity> {
+    let operand = record_var::<R::Operand>(operand, resolver).await?;
+    let result = match operand.value() {
+        Value::Bool(value) => *value,
+        value => {
+            return Err(Error::UnexpectedType {
+                expected: "Bool",
+                found: value.type_name(),
+            })
         }
     };
 
-    let value = value.ok_or_else(|| anyhow!("unable to resolve lhs"))?;
-    Ok(produce_final_value(value.clone(), calculations, resolver).await?)
+    Ok(R::predicate(operand, result))
 }
 
-pub async fn solve_one(comparison: &Comparison, resolver: &impl AsyncResolver) -> Result<bool> {
-    let left_variable = resolve_var(&comparison.what, resolver).await?;
-    let right_variable = resolve_var(&comparison.against, resolver).await?;
-
-    Ok(left_variable.compare(&right_variable, comparison.operator))
+#[async_recursion(?Send)]
+async fn record_entity<R>(entity: &Entity, resolver: &impl AsyncResolver) -> Result<R::Entity>
+where
+    R: Record,
+{
+    match entity {
+        Entity::Comparison(comparison) => record_one::<R>(comparison, resolver).await.or_else(R::unsolved),
+        Entity::Between(between) => record_between::<R>(between, resolver).await.or_else(R::unsolved),
+        Entity::Child(sequence) => record_tree::<R>(sequence, resolver).await,
+        Entity::Not(entity) => Ok(R::not(record_entity::<R>(entity, resolver).await?)),
+        Entity::Predicate(operand) => record_predicate::<R>(operand, resolver).await.or_else(R::unsolved),
+    }
 }
 
 #[async_recursion(?Send)]
-pub async fn solve_tree(sequence: &Sequence, resolver: &impl AsyncResolver) -> Result<bool> {
-    let mut result = true;
-
-    for entry in &sequence.items {
-        let (child_result, logic) = match entry {
-            Entity::Comparison(comparison, logic) => (solve_one(&comparison, resolver).await?, logic),
-            Entity::Child(sequence, logic) => (solve_tree(&sequence, resolver).await?, logic),
-        };
-
-        match logic {
-            Some(Logic::And) => result &= child_result,
-            Some(Logic::Or) => result |= child_result,
-            None => result = child_result,
+async fn record_tree<R>(sequence: &Sequence, resolver: &impl AsyncResolver) -> Result<R::Entity>
+where
+    R: Record,
+{
+    let mut items = R::Items::default();
+    let mut result = Tristate::from(sequence.logic == Logic::And);
+    for (index, entry) in sequence.items.iter().enumerate() {
+        let item = record_entity::<R>(entry, resolver).await?;
+        let solved = R::result(&item);
+        R::push(&mut items, item);
+        // Skip all remaining items as soon as the result can not change anymore. An unknown result is only the
+        // result if nothing decides the sequence.
+        match (sequence.logic, solved) {
+            (Logic::And, Tristate::False) | (Logic::Or, Tristate::True) => {
+                return Ok(R::sequence(sequence.logic, items, &sequence.items[index + 1..], solved))
+            }
+            (_, Tristate::Unknown) => result = Tristate::Unknown,
+            _ => {}
         }
     }
 
-    Ok(result)
+    Ok(R::sequence(sequence.logic, items, &[], result))
+}
+
+pub async fn solve_one(comparison: &Comparison, resolver: &impl AsyncResolver) -> Result<bool> {
+    record_one::<Solve>(comparison, resolver).await
+}
+
+pub async fn solve_between(between: &Between, resolver: &impl AsyncResolver) -> Result<bool> {
+    record_between::<Solve>(between, resolver).await
+}
+
+pub async fn solve_predicate(operand: &ComparisonType, resolver: &impl AsyncResolver) -> Result<bool> {
+    record_predicate::<Solve>(operand, resolver).await
+}
+
+pub async fn solve_tree(sequence: &Sequence, resolver: &impl AsyncResolver) -> Result<bool> {
+    record_tree::<Solve>(sequence, resolver).await
+}
+
+/// Async-version of [crate::solver::solve_tree_with_functions]
+pub async fn solve_tree_with_functions(
+    sequence: &Sequence,
+    resolver: &impl AsyncResolver,
+    functions: &AsyncFunctionRegistry,
+) -> Result<bool> {
+    solve_tree(sequence, &WithFunctions::new(resolver, functions)).await
+}
+
+pub async fn solve_tree_tristate(sequence: &Sequence, resolver: &impl AsyncResolver) -> Result<Tristate> {
+    record_tree::<SolveTristate>(sequence, resolver).await
+}
+
+pub async fn explain_tree(sequence: &Sequence, resolver: &impl AsyncResolver) -> Result<Explanation> {
+    record_tree::<Explain>(sequence, resolver).await
 }
 
 #[cfg(test)]
@@ -74,7 +178,11 @@ pub async fn solve_tree(sequence: &Sequence, resolver: &impl AsyncResolver) -> R
 async fn test_solve_async() -> Result<()> {
     use std::collections::HashMap;
 
-    use crate::{evaluate_async, MapResolver};
+    use crate::{
+        async_function::AsyncFunctionRegistry, evaluate_async, evaluate_tristate_async,
+        evaluate_tristate_with_options_async, evaluate_with_functions_async, evaluate_with_options_async,
+        explain_async, explain_with_options_async, function::ArgumentType, MapResolver, ParseOptions, Tristate,
+    };
 
     let mut values = HashMap::new();
     values.insert("a", 1);
@@ -83,14 +191,102 @@ async fn test_solve_async() -> Result<()> {
     values.insert("d", 4);
     let values: MapResolver = values.into();
 
-    assert_eq!(evaluate_async("a < 99", &values).await?, true);
-    assert_eq!(evaluate_async("a > 2", &values).await?, false);
-    assert_eq!(evaluate_async("b <= 2", &values).await?, true);
-    assert_eq!(evaluate_async("c >= 3", &values).await?, true);
+    assert!(evaluate_async("a < 99", &values).await?);
+    assert!(!evaluate_async("a > 2", &values).await?);
+    assert!(evaluate_async("b <= 2", &values).await?);
+    assert!(evaluate_async("c >= 3", &values).await?);
+    assert!(!evaluate_async("a == 4711 || ((b == 42 || b == 2) && (c == 3 && c == 4))", &values).await?);
+    assert!(evaluate_async("a == 1 || b == 9 && c == 9", &values).await?);
+    assert!(evaluate_async("!(a == 1 && b == 9) && not c == 9", &values).await?);
+    assert!(evaluate_async("a == 1 && true", &values).await?);
+    assert!(evaluate_async("b between a and c && !(d between a and c)", &values).await?);
+    assert!(evaluate_async("a", &values).await.is_err());
+    assert!(!evaluate_async("a == 2 && missing > 1", &values).await?);
+    assert!(evaluate_async("a == 1 || missing > 1", &values).await?);
+    assert_eq!(
+        evaluate_tristate_async("a == 1 || missing > 1", &values).await?,
+        Tristate::True
+    );
     assert_eq!(
-        evaluate_async("a == 4711 || ((b == 42 || b == 2) && (c == 3 && c == 4))", &values).await?,
-        false
+        evaluate_tristate_async("a == 1 && missing > 1", &values).await?,
+        Tristate::Unknown
+    );
+
+    let mut functions = AsyncFunctionRegistry::default();
+    functions.register("double", [ArgumentType::Number], |mut arguments| async move {
+        arguments
+            .remove(0)
+            .calculate(&Value::Integer(2), crate::Arithmetic::Mul)
+    });
+    assert!(evaluate_with_functions_async("double(b) == d && abs(-double(a)) == b", &values, &functions).await?);
+    assert!(
+        evaluate_with_functions_async("double(a)", &values, &AsyncFunctionRegistry::default())
+            .await
+            .is_err()
     );
 
+    // Without guessing, "1m" is a string instead of a duration
+    let no_guessing = ParseOptions { guess_literals: false };
+    assert!(evaluate_async(r#""1m" == s"1m""#, &values).await.is_err());
+    assert!(evaluate_with_options_async(r#""1m" == s"1m""#, &values, no_guessing, &functions).await?);
+    assert_eq!(
+        evaluate_tristate_with_options_async(r#""1m" == s"1m""#, &values, no_guessing).await?,
+        Tristate::True
+    );
+    let explanation = explain_with_options_async(r#""1m" == s"1m""#, &values, no_guessing).await?;
+    assert_eq!(explanation.result(), Some(true));
+
+    let explanation = explain_async("a == 2 && missing > 1", &values).await?;
+    assert_eq!(explanation.result(), Some(false));
+    assert_eq!(
+        explanation.to_string(),
+        "&& => false\n  a == 2 => false\n    a = 1\n    1 == 2\n  missing > 1 => short-circuited\n"
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg(feature = "async")]
+#[tokio::test]
+async fn test_solve_async_short_circuit() -> Result<()> {
+    use std::{collections::HashMap, sync::Mutex};
+
+    use crate::{evaluate_async, MapResolver};
+
+    /// Records all variable-names it has been asked for
+    struct RecordingResolver {
+        values: MapResolver,
+        resolved: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl AsyncResolver for RecordingResolver {
+        #[allow(mismatched_lifetime_syntaxes)]
+        async fn resolve<'a>(&self, name: impl AsRef<str> + Send + 'a) -> Option<&Value> {
+            self.resolved.lock().unwrap().push(name.as_ref().into());
+            AsyncResolver::resolve(&self.values, name).await
+        }
+    }
+
+    let mut values = HashMap::new();
+    values.insert("a", 1);
+    values.insert("b", 2);
+    let values = RecordingResolver {
+        values: MapResolver::from(values),
+        resolved: Mutex::new(Vec::new()),
+    };
+
+    assert!(!evaluate_async("a == 2 && (b == 2 || missing > 1)", &values).await?);
+    assert_eq!(*values.resolved.lock().unwrap(), vec!["a".to_string()]);
+
+    values.resolved.lock().unwrap().clear();
+    assert!(evaluate_async("a == 1 || b == 2 && missing > 1", &values).await?);
+    assert_eq!(*values.resolved.lock().unwrap(), vec!["a".to_string()]);
+
+    values.resolved.lock().unwrap().clear();
+    assert!(evaluate_async("(a == 2 && missing > 1) || b == 2", &values).await?);
+    assert_eq!(*values.resolved.lock().unwrap(), vec!["a".to_string(), "b".to_string()]);
+
     Ok(())
 }
@@ -1,6 +1,6 @@
-use anyhow::Result;
+use std::fmt::Display;
 
-use crate::value::Value;
+use crate::{compare::ComparisonType, error::Result};
 
 /// Enumerations for arethmetic opersions
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -13,13 +13,38 @@ pub enum Arithmetic {
     Div,
     /// `*` -> multiplication
     Mul,
+    /// `%` -> remainder of the euclidean division, which is never negative
+    Rem,
+    /// `**` or `^` -> exponentiation
+    Pow,
+    /// `//` -> euclidean integer division, which rounds down for a positive divisor
+    IntDiv,
 }
 
-/// Encapsulates a calculation on a given [Value] or a dynamically [crate::Resolver::resolve]d value.
+impl Display for Arithmetic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => write!(f, "+"),
+            Self::Sub => write!(f, "-"),
+            Self::Div => write!(f, "/"),
+            Self::Mul => write!(f, "*"),
+            Self::Rem => write!(f, "%"),
+            Self::Pow => write!(f, "**"),
+            Self::IntDiv => write!(f, "//"),
+        }
+    }
+}
+
+/// Encapsulates a calculation of two operands which themselves can be a [crate::Value], a dynamically
+/// [crate::Resolver::resolve]d value or another [Calculation].
 #[derive(Debug, PartialEq)]
-pub enum Calculation {
-    Value(Value, Arithmetic),
-    Variable(String, Arithmetic),
+pub struct Calculation {
+    /// Left-Hand-Side of the calculation
+    pub lhs: ComparisonType,
+    /// [Arithmetic] to apply on `lhs` and `rhs`
+    pub arithmetic: Arithmetic,
+    /// Right-Hand-Side of the calculation
+    pub rhs: ComparisonType,
 }
 
 /// Trait to guarantee that a given type is calculateable with [Arithmetic]
@@ -1,12 +1,31 @@
-use crate::{value::Value, Calculation};
+use std::fmt::Display;
+
+use regex::Regex;
+
+use crate::{
+    error::{Error, Result},
+    function::Call,
+    value::Value,
+    Arithmetic, Calculation,
+};
 
 /// Logic for comparisons
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub enum Logic {
+    #[default]
     And,
     Or,
 }
 
+impl Display for Logic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::And => write!(f, "&&"),
+            Self::Or => write!(f, "||"),
+        }
+    }
+}
+
 /// Comparison-operators
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Operator {
@@ -16,32 +35,192 @@ pub enum Operator {
     LessEqual,    // <=
     Greater,      // >
     Less,         // <
+    In,           // in
+    NotIn,        // not in
+    Contains,     // contains
+    StartsWith,   // starts_with
+    EndsWith,     // ends_with
+    Matches,      // =~
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let operator = match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::GreaterEqual => ">=",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::Less => "<",
+            Self::In => "in",
+            Self::NotIn => "not in",
+            Self::Contains => "contains",
+            Self::StartsWith => "starts_with",
+            Self::EndsWith => "ends_with",
+            Self::Matches => "=~",
+        };
+        write!(f, "{}", operator)
+    }
 }
 
 /// Helper to bind `compare` with our custom [Operator] to a struct
 pub trait Compareable {
-    /// Compare self with `against` using the given [Operator]
-    fn compare(&self, other: &Self, operator: Operator) -> bool;
+    /// Compare self with `against` using the given [Operator].
+    /// Fails if `self` and `other` can not be compared with each other.
+    fn compare(&self, other: &Self, operator: Operator) -> Result<bool>;
 }
 
-/// Defines if a comparison is against a value or against another variable
+/// One side of a [Comparison] which is a value, a variable or a calculation out of those
 #[derive(Debug, PartialEq)]
 pub enum ComparisonType {
-    /// A comparison of a variable against a fixed value (wich optional calculations)
-    Value(Value, Vec<Calculation>),
-    /// A comparison of a variable against an other variable (wich optional calculations)
-    Variable(String, Vec<Calculation>),
+    /// A fixed value
+    Value(Value),
+    /// A variable which is [crate::Resolver::resolve]d on solving
+    Variable(String),
+    /// A [Calculation] on two other [ComparisonType]s
+    Calculation(Box<Calculation>),
+    /// The negative of another [ComparisonType] (e.G. `-a`)
+    Negative(Box<ComparisonType>),
+    /// The first [ComparisonType] or the second one if the first contains a variable which can not be
+    /// [crate::Resolver::resolve]d or is [Value::Null] (`a ?? b`)
+    Coalesce(Box<ComparisonType>, Box<ComparisonType>),
+    /// [Value::Bool] whether the variable can be [crate::Resolver::resolve]d (`exists(a)`)
+    Exists(String),
+    /// The result of a function [Call] (`abs(a - b)`)
+    Call(Call),
+    /// The pattern of a regular expression match whose right-hand side is a string literal (`a =~ "^v1\."`), which
+    /// has been compiled on parsing. Solves to the [Value::String] of the pattern. A pattern in any other form (e.G. a
+    /// variable or a calculation) stays as it is and is compiled on every solving.
+    Pattern(Pattern),
 }
 
 impl ComparisonType {
-    pub fn with_calculation(&mut self, calculation: Calculation) {
+    /// Apply `arithmetic` on `self` and `rhs`, producing a [ComparisonType::Calculation]
+    pub fn with_calculation(self, arithmetic: Arithmetic, rhs: ComparisonType) -> Self {
+        Self::Calculation(Box::new(Calculation {
+            lhs: self,
+            arithmetic,
+            rhs,
+        }))
+    }
+
+    /// Fall back to `fallback` if `self` can not be resolved, producing a [ComparisonType::Coalesce]
+    pub fn with_fallback(self, fallback: ComparisonType) -> Self {
+        Self::Coalesce(Box::new(self), Box::new(fallback))
+    }
+
+    /// Apply `check` on every function [Call] within `self`, including calls in arguments of other calls
+    pub(crate) fn check_calls(&self, check: &mut impl FnMut(&Call) -> Result<()>) -> Result<()> {
         match self {
-            Self::Value(_, calculations) => calculations.push(calculation),
-            Self::Variable(_, calculations) => calculations.push(calculation),
+            Self::Calculation(calculation) => {
+                calculation.lhs.check_calls(check)?;
+                calculation.rhs.check_calls(check)
+            }
+            Self::Negative(operand) => operand.check_calls(check),
+            Self::Coalesce(operand, fallback) => {
+                operand.check_calls(check)?;
+                fallback.check_calls(check)
+            }
+            Self::Call(call) => {
+                check(call)?;
+                call.arguments
+                    .iter()
+                    .try_for_each(|argument| argument.check_calls(check))
+            }
+            Self::Value(_) | Self::Variable(_) | Self::Exists(_) | Self::Pattern(_) => Ok(()),
+        }
+    }
+
+    /// Whether this is a negation or a negative literal, which is written with a leading `-`
+    fn is_negative(&self) -> bool {
+        match self {
+            Self::Negative(_) => true,
+            Self::Value(Value::Numeric(value)) => value.is_sign_negative(),
+            Self::Value(Value::Integer(value)) => *value < 0,
+            Self::Value(Value::Duration(value)) => *value < chrono::Duration::zero(),
+            _ => false,
+        }
+    }
+}
+
+/// Writes the [ComparisonType] as expression. Nested calculations are always parenthesised.
+impl Display for ComparisonType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(value) => value.fmt_literal(f),
+            Self::Variable(name) => write!(f, "{}", name),
+            // A negative base is parenthesised as exponentiation binds tighter than a negation (`(-2) ** 2`)
+            Self::Calculation(calculation)
+                if calculation.arithmetic == Arithmetic::Pow && calculation.lhs.is_negative() =>
+            {
+                write!(f, "({}) ** {}", calculation.lhs, Parenthesised(&calculation.rhs))
+            }
+            Self::Calculation(calculation) => write!(
+                f,
+                "{} {} {}",
+                Parenthesised(&calculation.lhs),
+                calculation.arithmetic,
+                Parenthesised(&calculation.rhs)
+            ),
+            Self::Negative(operand) => write!(f, "-{}", Parenthesised(operand)),
+            Self::Coalesce(operand, fallback) => write!(f, "{} ?? {}", operand, Parenthesised(fallback)),
+            Self::Exists(name) => write!(f, "exists({})", name),
+            Self::Call(call) => write!(f, "{}", call),
+            Self::Pattern(pattern) => pattern.value().fmt_literal(f),
         }
     }
 }
 
+/// Writes a [ComparisonType] in parentheses if it is a [ComparisonType::Calculation] or [ComparisonType::Coalesce]
+struct Parenthesised<'a>(&'a ComparisonType);
+
+impl Display for Parenthesised<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            ComparisonType::Calculation(_) | ComparisonType::Coalesce(..) => write!(f, "({})", self.0),
+            operand => write!(f, "{}", operand),
+        }
+    }
+}
+
+impl From<Value> for ComparisonType {
+    fn from(value: Value) -> Self {
+        Self::Value(value)
+    }
+}
+
+/// A compiled regular expression. Equal to another [Pattern] with the same expression.
+#[derive(Debug, Clone)]
+pub struct Pattern(Regex);
+
+impl Pattern {
+    /// Compile the regular expression `pattern`
+    pub fn new(pattern: &str) -> Result<Self> {
+        Regex::new(pattern)
+            .map(Self)
+            .map_err(|error| Error::InvalidRegex(error.to_string()))
+    }
+
+    /// The pattern as [Value::String]
+    pub fn value(&self) -> Value {
+        Value::String(self.0.as_str().into())
+    }
+
+    /// Whether the [Value::String] `value` matches this [Pattern]. Fails for any other type.
+    pub fn is_match(&self, value: &Value) -> Result<bool> {
+        match value {
+            Value::String(value) => Ok(self.0.is_match(value)),
+            value => Err(Error::incompatible_types(Operator::Matches, value, &self.value())),
+        }
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Comparison {
     /// Left-Hand-Side of the comparison (which the rhs will be compared to)
@@ -52,13 +231,48 @@ pub struct Comparison {
     pub against: ComparisonType,
 }
 
+impl Comparison {
+    /// Compare the solved values of `what` and `against` with the [Operator], using the compiled [Pattern] for a
+    /// regular expression match if there is one
+    pub(crate) fn compare_values(&self, what: &Value, against: &Value) -> Result<bool> {
+        match (&self.against, self.operator) {
+            (ComparisonType::Pattern(pattern), Operator::Matches) => pattern.is_match(what),
+            _ => what.compare(against, self.operator),
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.what, self.operator, self.against)
+    }
+}
+
+/// Whether `what` lies within the range from `from` to `to` including both ends (`now between "22:00:00" and "06:00:00"`).
+/// A range of [Value::Time]s wraps around midnight if `from` is later than `to`, see [Value::between].
+#[derive(Debug, PartialEq)]
+pub struct Between {
+    /// The operand to look for within the range
+    pub what: ComparisonType,
+    /// Start of the range
+    pub from: ComparisonType,
+    /// End of the range
+    pub to: ComparisonType,
+}
+
+impl Display for Between {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} between {} and {}", self.what, self.from, self.to)
+    }
+}
+
 /// Triplet (variable-name, operator, value) to [Comparison] conversion
 impl From<(&str, Operator, Value)> for Comparison {
     fn from((variable_name, operator, value): (&str, Operator, Value)) -> Self {
         Self {
-            what: ComparisonType::Variable(variable_name.into(), Vec::new()),
+            what: ComparisonType::Variable(variable_name.into()),
             operator,
-            against: ComparisonType::Value(value, Vec::new()),
+            against: ComparisonType::Value(value),
         }
     }
 }
@@ -67,9 +281,9 @@ impl From<(&str, Operator, Value)> for Comparison {
 impl From<(&str, Operator, &str)> for Comparison {
     fn from((variable_name, operator, against_variable_name): (&str, Operator, &str)) -> Self {
         Self {
-            what: ComparisonType::Variable(variable_name.into(), Vec::new()),
+            what: ComparisonType::Variable(variable_name.into()),
             operator,
-            against: ComparisonType::Variable(against_variable_name.into(), Vec::new()),
+            against: ComparisonType::Variable(against_variable_name.into()),
         }
     }
 }
@@ -0,0 +1,116 @@
+use thiserror::Error;
+
+use crate::{function::Arity, value::Value};
+
+/// Errors which can occur on parsing or solving a [crate::Sequence]
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum Error {
+    /// The input could not be parsed at byte-`offset` (1-based `line` and `column`), where one of `expected`
+    /// would have been valid. See [Error::render] for a printable diagnostic.
+    #[error("Syntax error at line {line}, column {column}: expected {}", join_expected(expected))]
+    Syntax {
+        offset: usize,
+        line: usize,
+        column: usize,
+        expected: Vec<&'static str>,
+    },
+    /// The [crate::Resolver] has no value for the variable
+    #[error("Unable to resolve variable '{0}'")]
+    UnresolvedVariable(String),
+    /// `op` can not be applied on values of type `lhs` and `rhs`
+    #[error("Incompatible types for '{op}': {lhs} and {rhs}")]
+    IncompatibleTypes {
+        op: String,
+        lhs: &'static str,
+        rhs: &'static str,
+    },
+    /// `op` can not be applied on a value of type `value`
+    #[error("Incompatible type for '{op}': {value}")]
+    IncompatibleType { op: String, value: &'static str },
+    /// A value of type `expected` was needed but the value is of type `found`
+    #[error("Expected a value of type {expected} but found {found}")]
+    UnexpectedType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The string `value` can not be read as a value of type `to` (e.G. `duration("soon")`)
+    #[error("Unable to read '{value}' as {to}")]
+    InvalidConversion { to: &'static str, value: String },
+    /// Division (of a numeric or a duration) by zero
+    #[error("Division by zero")]
+    DivisionByZero,
+    /// The result of an operation can not be represented
+    #[error("Value out of range: {0}")]
+    OutOfRange(String),
+    /// There is no function called like this
+    #[error("Unknown function '{0}'")]
+    UnknownFunction(String),
+    /// The `function` takes `expected` arguments but was called with `found` arguments
+    #[error("Function '{function}' takes {expected} argument(s) but was called with {found}")]
+    WrongArity {
+        function: String,
+        expected: Arity,
+        found: usize,
+    },
+    /// The pattern of a regular expression match (`=~`) is invalid
+    #[error("Invalid regular expression: {0}")]
+    InvalidRegex(String),
+}
+
+impl Error {
+    /// [Error::Syntax] at byte-`offset` of `source`
+    pub fn syntax(source: &str, offset: usize, expected: Vec<&'static str>) -> Self {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        Self::Syntax {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            expected,
+        }
+    }
+
+    /// Renders this error for `source` which is the input that caused it. For [Error::Syntax], the failing line is
+    /// printed with a `^` under the failing position; all other errors render as their message.
+    /// ```
+    /// use metrics_evaluation::*;
+    ///
+    /// let input = "a == 1 && b >";
+    /// let error = parse_tree(input).unwrap_err();
+    /// assert_eq!(
+    ///     error.render(input),
+    ///     "Syntax error at line 1, column 14: expected -, value, variable or (\n\
+    ///      a == 1 && b >\n\
+    ///      \x20            ^"
+    /// );
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        match self {
+            Self::Syntax { line, column, .. } => {
+                let text = source.lines().nth(line - 1).unwrap_or_default();
+                format!("{}\n{}\n{}^", self, text, " ".repeat(column - 1))
+            }
+            error => error.to_string(),
+        }
+    }
+
+    /// [Error::IncompatibleTypes] for applying `op` on `lhs` and `rhs`
+    pub fn incompatible_types(op: impl ToString, lhs: &Value, rhs: &Value) -> Self {
+        Self::IncompatibleTypes {
+            op: op.to_string(),
+            lhs: lhs.type_name(),
+            rhs: rhs.type_name(),
+        }
+    }
+}
+
+fn join_expected(expected: &[&str]) -> String {
+    match expected.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => "nothing".into(),
+    }
+}
+
+/// [std::result::Result] with this crate's [Error](enum@Error)
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,394 @@
+use std::fmt::Display;
+
+use crate::{
+    calculate::Arithmetic,
+    compare::{ComparisonType, Logic, Operator},
+    record::{Record, RecordOperand},
+    sequence::Entity,
+    tristate::Tristate,
+    value::Value,
+};
+
+/// A solved operand of a [crate::compare::Comparison] or a predicate, mirroring [crate::compare::ComparisonType]
+/// with the values that have been resolved or calculated on solving.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// A fixed value
+    Value(Value),
+    /// A variable and the value it was resolved to
+    Variable { name: String, value: Value },
+    /// A calculation step on two other [Operand]s and its result
+    Calculation {
+        lhs: Box<Operand>,
+        arithmetic: Arithmetic,
+        rhs: Box<Operand>,
+        value: Value,
+    },
+    /// The negative of another [Operand] and its result
+    Negative { operand: Box<Operand>, value: Value },
+    /// A coalescing (`a ?? b`) of `expression`, where `used` is the [Operand] whose value was taken. If the
+    /// fallback was used, `missing` is the expression that could not be resolved or was [Value::Null].
+    Coalesce {
+        expression: String,
+        used: Box<Operand>,
+        missing: Option<String>,
+    },
+    /// Whether the variable `name` can be resolved (`exists(name)`)
+    Exists { name: String, value: Value },
+    /// A function call on other [Operand]s and its result
+    Call {
+        name: String,
+        arguments: Vec<Operand>,
+        value: Value,
+    },
+}
+
+impl Operand {
+    /// The final value of this [Operand]
+    pub fn value(&self) -> &Value {
+        match self {
+            Self::Value(value)
+            | Self::Variable { value, .. }
+            | Self::Calculation { value, .. }
+            | Self::Negative { value, .. }
+            | Self::Exists { value, .. }
+            | Self::Call { value, .. } => value,
+            Self::Coalesce { used, .. } => used.value(),
+        }
+    }
+
+    /// Write every resolved variable and calculation step, innermost first
+    fn fmt_steps(&self, f: &mut std::fmt::Formatter<'_>, indent: usize) -> std::fmt::Result {
+        match self {
+            Self::Value(_) => return Ok(()),
+            Self::Variable { .. } => {}
+            Self::Calculation { lhs, rhs, .. } => {
+                lhs.fmt_steps(f, indent)?;
+                rhs.fmt_steps(f, indent)?;
+            }
+            Self::Negative { operand, .. } => operand.fmt_steps(f, indent)?,
+            Self::Coalesce { used, missing, .. } => {
+                if let Some(missing) = missing {
+                    writeln!(f, "{:indent$}{} has no value", "", missing, indent = indent)?;
+                }
+                used.fmt_steps(f, indent)?;
+            }
+            Self::Exists { .. } => {}
+            Self::Call { arguments, .. } => {
+                for argument in arguments {
+                    argument.fmt_steps(f, indent)?;
+                }
+            }
+        }
+        writeln!(f, "{:indent$}{} = {}", "", self, self.value(), indent = indent)
+    }
+}
+
+/// Writes the [Operand] as expression like [crate::compare::ComparisonType] does
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(value) => value.fmt_literal(f),
+            Self::Variable { name, .. } => write!(f, "{}", name),
+            Self::Calculation {
+                lhs, arithmetic, rhs, ..
+            } => write!(f, "{} {} {}", Parenthesised(lhs), arithmetic, Parenthesised(rhs)),
+            Self::Negative { operand, .. } => write!(f, "-{}", Parenthesised(operand)),
+            Self::Coalesce { expression, .. } => write!(f, "{}", expression),
+            Self::Exists { name, .. } => write!(f, "exists({})", name),
+            Self::Call { name, arguments, .. } => {
+                write!(f, "{}(", name)?;
+                for (index, argument) in arguments.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", argument)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Writes an [Operand] in parentheses if it is an [Operand::Calculation] or [Operand::Coalesce]
+struct Parenthesised<'a>(&'a Operand);
+
+impl Display for Parenthesised<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Operand::Calculation { .. } | Operand::Coalesce { .. } => write!(f, "({})", self.0),
+            operand => write!(f, "{}", operand),
+        }
+    }
+}
+
+/// Trace of solving a [crate::Sequence], mirroring its structure. Created by [crate::explain_tree].
+/// Its [Display] renders a human-readable tree which tells why the [crate::Sequence] solved to its result.
+/// ```
+/// use std::collections::HashMap;
+///
+/// use metrics_evaluation::*;
+///
+/// let mut values = HashMap::new();
+/// values.insert("a", 1);
+/// values.insert("b", 2);
+/// let values: MapResolver = values.into();
+///
+/// let explanation = explain("(a + b) * 2 > 5 || b == 99", &values).unwrap();
+/// assert_eq!(explanation.result(), Some(true));
+/// assert_eq!(
+///     explanation.to_string(),
+///     "\
+/// || => true
+///   (a + b) * 2 > 5 => true
+///     a = 1
+///     b = 2
+///     a + b = 3
+///     (a + b) * 2 = 6
+///     6 > 5
+///   b == 99 => short-circuited
+/// "
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Explanation {
+    /// A [crate::Sequence] and the explanations of its items
+    Sequence {
+        logic: Logic,
+        items: Vec<Explanation>,
+        result: bool,
+    },
+    /// A [crate::compare::Comparison] of two solved [Operand]s
+    Comparison {
+        what: Operand,
+        operator: Operator,
+        against: Operand,
+        result: bool,
+    },
+    /// A [crate::compare::Between] of three solved [Operand]s
+    Between {
+        what: Operand,
+        from: Operand,
+        to: Operand,
+        result: bool,
+    },
+    /// The negation of another [Explanation]
+    Not { entity: Box<Explanation>, result: bool },
+    /// A predicate and the [Operand] it solved to
+    Predicate { operand: Operand, result: bool },
+    /// An item that was not solved because the result of its [crate::Sequence] was already known.
+    /// Holds the expression of the skipped item.
+    ShortCircuited(String),
+}
+
+impl Explanation {
+    /// The result of this part of the [crate::Sequence] or [None] if it was short-circuited
+    pub fn result(&self) -> Option<bool> {
+        match self {
+            Self::Sequence { result, .. }
+            | Self::Comparison { result, .. }
+            | Self::Between { result, .. }
+            | Self::Not { result, .. }
+            | Self::Predicate { result, .. } => Some(*result),
+            Self::ShortCircuited(_) => None,
+        }
+    }
+
+    /// Whether this part of the [crate::Sequence] was skipped
+    pub fn is_short_circuited(&self) -> bool {
+        matches!(self, Self::ShortCircuited(_))
+    }
+
+    fn fmt_indented(&self, f: &mut std::fmt::Formatter<'_>, indent: usize) -> std::fmt::Result {
+        match self {
+            Self::Sequence { logic, items, result } => {
+                writeln!(f, "{:indent$}{} => {}", "", logic, result, indent = indent)?;
+                for item in items {
+                    item.fmt_indented(f, indent + 2)?;
+                }
+            }
+            Self::Comparison {
+                what,
+                operator,
+                against,
+                result,
+            } => {
+                writeln!(
+                    f,
+                    "{:indent$}{} {} {} => {}",
+                    "",
+                    what,
+                    operator,
+                    against,
+                    result,
+                    indent = indent
+                )?;
+                what.fmt_steps(f, indent + 2)?;
+                against.fmt_steps(f, indent + 2)?;
+                writeln!(
+                    f,
+                    "{:indent$}{} {} {}",
+                    "",
+                    what.value(),
+                    operator,
+                    against.value(),
+                    indent = indent + 2
+                )?;
+            }
+            Self::Between { what, from, to, result } => {
+                writeln!(
+                    f,
+                    "{:indent$}{} between {} and {} => {}",
+                    "",
+                    what,
+                    from,
+                    to,
+                    result,
+                    indent = indent
+                )?;
+                what.fmt_steps(f, indent + 2)?;
+                from.fmt_steps(f, indent + 2)?;
+                to.fmt_steps(f, indent + 2)?;
+                writeln!(
+                    f,
+                    "{:indent$}{} between {} and {}",
+                    "",
+                    what.value(),
+                    from.value(),
+                    to.value(),
+                    indent = indent + 2
+                )?;
+            }
+            Self::Not { entity, result } => {
+                writeln!(f, "{:indent$}! => {}", "", result, indent = indent)?;
+                entity.fmt_indented(f, indent + 2)?;
+            }
+            Self::Predicate { operand, result } => {
+                writeln!(f, "{:indent$}{} => {}", "", operand, result, indent = indent)?;
+                operand.fmt_steps(f, indent + 2)?;
+            }
+            Self::ShortCircuited(expression) => {
+                writeln!(f, "{:indent$}{} => short-circuited", "", expression, indent = indent)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Records every step of solving into [Operand]s and [Explanation]s
+pub(crate) struct Explain;
+
+impl Record for Explain {
+    type Operand = Operand;
+    type Entity = Explanation;
+    type Items = Vec<Explanation>;
+
+    fn result(entity: &Explanation) -> Tristate {
+        entity.result().map_or(Tristate::Unknown, Tristate::from)
+    }
+
+    fn comparison(what: Operand, operator: Operator, against: Operand, result: bool) -> Explanation {
+        Explanation::Comparison {
+            what,
+            operator,
+            against,
+            result,
+        }
+    }
+
+    fn between(what: Operand, from: Operand, to: Operand, result: bool) -> Explanation {
+        Explanation::Between { what, from, to, result }
+    }
+
+    fn not(entity: Explanation) -> Explanation {
+        let result = entity.result() == Some(false);
+        Explanation::Not {
+            entity: Box::new(entity),
+            result,
+        }
+    }
+
+    fn predicate(operand: Operand, result: bool) -> Explanation {
+        Explanation::Predicate { operand, result }
+    }
+
+    fn push(items: &mut Vec<Explanation>, item: Explanation) {
+        items.push(item);
+    }
+
+    fn sequence(logic: Logic, mut items: Vec<Explanation>, skipped: &[Entity], result: Tristate) -> Explanation {
+        items.extend(
+            skipped
+                .iter()
+                .map(|entry| Explanation::ShortCircuited(entry.to_string())),
+        );
+        Explanation::Sequence {
+            logic,
+            items,
+            // Without three-valued logic every solved item is known
+            result: result == Tristate::True,
+        }
+    }
+}
+
+impl RecordOperand for Operand {
+    fn value(&self) -> &Value {
+        Operand::value(self)
+    }
+
+    fn fixed(value: Value) -> Self {
+        Self::Value(value)
+    }
+
+    fn variable(name: &str, value: Value) -> Self {
+        Self::Variable {
+            name: name.into(),
+            value,
+        }
+    }
+
+    fn calculation(lhs: Self, arithmetic: Arithmetic, rhs: Self, value: Value) -> Self {
+        Self::Calculation {
+            lhs: Box::new(lhs),
+            arithmetic,
+            rhs: Box::new(rhs),
+            value,
+        }
+    }
+
+    fn negative(operand: Self, value: Value) -> Self {
+        Self::Negative {
+            operand: Box::new(operand),
+            value,
+        }
+    }
+
+    fn coalesce(expression: &ComparisonType, used: Self, missing: Option<&ComparisonType>) -> Self {
+        Self::Coalesce {
+            expression: expression.to_string(),
+            used: Box::new(used),
+            missing: missing.map(ToString::to_string),
+        }
+    }
+
+    fn exists(name: &str, value: Value) -> Self {
+        Self::Exists {
+            name: name.into(),
+            value,
+        }
+    }
+
+    fn call(name: &str, arguments: Vec<Self>, value: Value) -> Self {
+        Self::Call {
+            name: name.into(),
+            arguments,
+            value,
+        }
+    }
+}
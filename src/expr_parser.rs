@@ -1,208 +1,504 @@
-use anyhow::{anyhow, Result};
-use chrono::naive::NaiveTime;
 use nom::{
     branch::alt,
-    bytes::complete::{is_not, tag},
-    character::{
-        complete::{alpha1, alphanumeric1, char},
-        streaming::multispace0,
-    },
-    combinator::{recognize, value},
-    error::ParseError,
-    multi::{many0, many1},
-    sequence::{delimited, pair, preceded, tuple},
-    IResult,
+    bytes::complete::tag,
+    character::complete::{alpha1, alphanumeric1, char, digit0, digit1, multispace0, multispace1, one_of},
+    combinator::{cut, map, not, opt, peek, recognize, value},
+    error::{ErrorKind, ParseError},
+    multi::{many0, separated_list0},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult, Offset,
 };
 use parse_hyperlinks::take_until_unbalanced;
 
 use crate::{
     calculate::Arithmetic,
-    compare::{Comparison, ComparisonType, Logic, Operator},
+    compare::{Between, Comparison, ComparisonType, Logic, Operator, Pattern},
+    error::{Error, Result},
+    function::{Call, FunctionRegistry, Signatures},
     sequence::{Entity, Sequence},
     value::Value,
-    Calculation,
 };
 
 const TRUE: &str = "true";
 const FALSE: &str = "false";
+const NULL: &str = "null";
+
+/// What is expected where a [Entity] has to start
+const EXPECTED_ENTITY: &[&str] = &["!", "-", "value", "variable", "("];
+/// What is expected between two [Entity]s
+const EXPECTED_LOGIC: &[&str] = &["&&", "||"];
+
+/// A failure of a matcher at the position `input` where one of `expected` would have been valid.
+/// On alternatives, the failure that made it furthest into the input is kept, so `expected` tells what
+/// would have been needed to get any further.
+#[derive(Debug, PartialEq)]
+pub struct ParserError<'a> {
+    /// The remaining input at the failing position
+    pub input: &'a str,
+    /// Descriptions of the tokens which would have been valid at `input`
+    pub expected: Vec<&'static str>,
+}
+
+impl<'a> ParserError<'a> {
+    fn expected(input: &'a str, token: &'static str) -> Self {
+        Self {
+            input: input.trim_start(),
+            expected: vec![token],
+        }
+    }
+
+    fn from_nom(error: nom::Err<Self>, input: &'a str) -> Self {
+        match error {
+            nom::Err::Error(error) | nom::Err::Failure(error) => error,
+            nom::Err::Incomplete(_) => Self::from_error_kind(input, ErrorKind::Complete),
+        }
+    }
+}
+
+impl<'a> ParseError<&'a str> for ParserError<'a> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        Self {
+            input,
+            expected: Vec::new(),
+        }
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        match self.input.len().cmp(&other.input.len()) {
+            std::cmp::Ordering::Less => self,
+            std::cmp::Ordering::Greater => other,
+            std::cmp::Ordering::Equal => {
+                for token in other.expected {
+                    if !self.expected.contains(&token) {
+                        self.expected.push(token);
+                    }
+                }
+                self
+            }
+        }
+    }
+}
+
+/// [IResult] of all matchers in here
+pub type MatchResult<'a, O> = IResult<&'a str, O, ParserError<'a>>;
 
-fn match_optional_logic(input: &str) -> IResult<&str, Vec<&str>> {
+/// Replaces any failure of `parser` with the expectation of `token`
+fn expect<'a, O, F>(token: &'static str, parser: F) -> impl Fn(&'a str) -> MatchResult<'a, O>
+where
+    F: Fn(&'a str) -> MatchResult<'a, O>,
+{
+    move |input| parser(input).map_err(|error| error.map(|_| ParserError::expected(input, token)))
+}
+
+/// Matches exactly `token`
+fn token<'a>(token: &'static str) -> impl Fn(&'a str) -> MatchResult<'a, &'a str> {
+    expect(token, move |input| tag(token)(input))
+}
+
+fn match_optional_logic(input: &str) -> MatchResult<'_, Vec<&str>> {
     // TODO: isnt there something like `one0`?
     let (rest, m) = many0(alt((
-        trim(tag("or")),
-        trim(tag("and")),
-        trim(tag("||")),
-        trim(tag("&&")),
+        trim(match_keyword("or")),
+        trim(match_keyword("and")),
+        trim(token("||")),
+        trim(token("&&")),
     )))(input)?;
     Ok((rest, m))
 }
 
-fn match_optional_calculation(input: &str) -> IResult<&str, Vec<&str>> {
-    // TODO: isnt there something like `one0`?
-    let (rest, m) = many0(alt((trim(tag("+")), trim(tag("-")), trim(tag("*")), trim(tag("/")))))(input)?;
-    Ok((rest, m))
+/// Matches any leading negations (`!` or `not`) and returns their count
+fn match_negations(input: &str) -> MatchResult<'_, usize> {
+    let (rest, m) = many0(alt((
+        trim(match_keyword("not")),
+        trim(|input| terminated(tag("!"), not(tag("=")))(input)),
+    )))(input)?;
+    Ok((rest, m.len()))
 }
 
-fn match_identifier(input: &str) -> IResult<&str, &str> {
-    let (rest, m) = recognize(pair(
-        alt((alpha1, tag("."), tag("_"))),
-        many0(alt((alphanumeric1, tag("."), tag("_")))),
-    ))(input)?;
+fn match_identifier(input: &str) -> MatchResult<'_, &str> {
+    let (rest, m) = expect("variable", |input| {
+        recognize(pair(alt((alpha1, tag("."), tag("_"))), many0(match_identifier_char)))(input)
+    })(input)?;
     Ok((rest, m))
 }
 
-fn match_value(input: &str) -> IResult<&str, Value> {
-    let (rest, value) = recognize(many1(alphanumeric1))(input)?;
+/// Matches characters which may continue an identifier
+fn match_identifier_char(input: &str) -> MatchResult<'_, &str> {
+    alt((alphanumeric1, tag("."), tag("_")))(input)
+}
 
-    let value = match value {
-        TRUE => true.into(),
-        FALSE => false.into(),
-        _ => str::parse::<f64>(value)
-            .map_err(|_| nom::Err::Incomplete(nom::Needed::Unknown))?
-            .into(),
-    };
+/// Matches `keyword` only if it is not the beginning of an identifier (e.G. `true` but not `true_flag`)
+fn match_keyword<'a>(keyword: &'static str) -> impl Fn(&'a str) -> MatchResult<'a, &'a str> {
+    move |input| terminated(tag(keyword), not(match_identifier_char))(input)
+}
+
+/// Matches an unsigned decimal number with optional fraction and exponent, like `42`, `20.5`, `.5` or `1e-3`
+fn match_number(input: &str) -> MatchResult<'_, Value> {
+    let (rest, number) = terminated(
+        recognize(pair(
+            alt((
+                recognize(pair(digit1, opt(pair(char('.'), digit0)))),
+                recognize(pair(char('.'), digit1)),
+            )),
+            opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+        )),
+        not(match_identifier_char),
+    )(input)?;
+
+    let value = Value::parse_number(number)
+        .ok_or_else(|| nom::Err::Error(ParserError::from_error_kind(input, ErrorKind::Float)))?;
 
     Ok((rest, value))
 }
 
-fn match_string_literal(input: &str) -> IResult<&str, &str> {
-    let (rest, m) = recognize(delimited(
-        alt((char('"'), char('\''))),
-        many1(is_not("\"")),
-        alt((char('"'), char('\''))),
-    ))(input)?;
-    Ok((rest, &m[1..m.len() - 1]))
+fn match_value(input: &str) -> MatchResult<'_, Value> {
+    alt((
+        value(Value::Bool(true), match_keyword(TRUE)),
+        value(Value::Bool(false), match_keyword(FALSE)),
+        value(Value::Null, match_keyword(NULL)),
+        match_number,
+    ))(input)
 }
 
-fn match_string_type(input: &str) -> IResult<&str, Value> {
-    let (rest, value) = match_string_literal(input)?;
+/// Matches a string literal in double or single quotes which must be the same on both ends (`"it's"`,
+/// `'say "hi"'`, `""`) and returns its unescaped text. See [match_escape] for the escape sequences, a backslash
+/// which does not start one of them is kept as it is.
+fn match_string_literal(input: &str) -> MatchResult<'_, String> {
+    let (quote, closing) = match input.chars().next() {
+        Some('"') => ('"', "\""),
+        Some('\'') => ('\'', "'"),
+        _ => return Err(nom::Err::Error(ParserError::expected(input, "value"))),
+    };
 
-    let value = humantime::parse_duration(value)
-        .map(|duration| Value::from(duration))
-        .or_else(|_| NaiveTime::parse_from_str(&value, "%H:%M:%S").map(|time| Value::from(time)))
-        .unwrap_or_else(|_| Value::from(value));
+    let mut text = String::new();
+    let mut rest = &input[1..];
+    loop {
+        let mut chars = rest.chars();
+        match chars.next() {
+            None => return Err(nom::Err::Error(ParserError::expected(rest, closing))),
+            Some(c) if c == quote => return Ok((chars.as_str(), text)),
+            Some('\\') => match match_escape(chars.as_str()) {
+                Some((after, c)) => {
+                    text.push(c);
+                    rest = after;
+                }
+                None if chars.as_str().starts_with("u{") => {
+                    return Err(nom::Err::Error(ParserError::expected(rest, "escape sequence")))
+                }
+                // Any other backslash is kept, so regular expressions can be written as usual (`"^v1\.\d+"`)
+                None => {
+                    text.push('\\');
+                    rest = chars.as_str();
+                }
+            },
+            Some(c) => {
+                text.push(c);
+                rest = chars.as_str();
+            }
+        }
+    }
+}
+
+/// Matches the rest of an escape sequence after its `\` like in rust: `\"`, `\'`, `\\`, `\n`, `\r`, `\t`, `\0`
+/// or `\u{..}` with up to six hex digits of a unicode character
+fn match_escape(input: &str) -> Option<(&str, char)> {
+    let mut chars = input.chars();
+    let c = match chars.next()? {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        '0' => '\0',
+        c @ ('"' | '\'' | '\\') => c,
+        'u' => {
+            let rest = chars.as_str().strip_prefix('{')?;
+            let (hex, rest) = rest.split_at(rest.find('}')?);
+            if hex.is_empty() || hex.len() > 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            let c = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)?;
+            return Some((&rest[1..], c));
+        }
+        _ => return None,
+    };
+    Some((chars.as_str(), c))
+}
+
+/// Matches a string literal which is guessed to be of another type if it reads like one (see [ParseOptions])
+fn match_string_type(input: &str, options: ParseOptions) -> MatchResult<'_, Value> {
+    let (rest, text) = match_string_literal(input)?;
+    let value = match options.guess_literals {
+        true => Value::guess(text),
+        false => Value::String(text),
+    };
 
     Ok((rest, value))
 }
 
-fn match_value_type(input: &str) -> IResult<&str, Value> {
-    let (rest, value) = alt((match_value, match_string_type))(input)?;
+/// Matches a literal of an explicit type which is never guessed: `d"5min"` is a [Value::Duration], `t"08:00:00"` a
+/// [Value::Time], `date"2024-05-01"` a [Value::Date], `datetime"2024-05-01T08:00:00+02:00"` a [Value::DateTime] and
+/// `s"1m"` a [Value::String]
+fn match_typed_literal(input: &str) -> MatchResult<'_, Value> {
+    let (literal, prefix) = expect("value", |input| {
+        let prefix = alt((tag("datetime"), tag("date"), recognize(one_of("dts"))));
+        terminated(prefix, peek(one_of("\"'")))(input)
+    })(input)?;
+    let (rest, text) = cut(match_string_literal)(literal)?;
+    let (value, expected) = match prefix {
+        "d" => (Value::parse_duration(&text), "duration"),
+        "t" => (Value::parse_time(&text), "time"),
+        "date" => (Value::parse_date(&text), "date"),
+        "datetime" => (Value::parse_date_time(&text), "datetime"),
+        _ => (Some(Value::String(text)), "string"),
+    };
+    let value = value.ok_or_else(|| nom::Err::Failure(ParserError::expected(literal, expected)))?;
+
     Ok((rest, value))
 }
 
+fn match_value_type(input: &str, options: ParseOptions) -> MatchResult<'_, Value> {
+    // A broken string literal reports where it broke, anything else that there is no value at all
+    let (rest, value) = alt((expect("value", match_value), match_typed_literal, |input| {
+        match_string_type(input, options)
+    }))(input)?;
+    Ok((rest, value))
+}
+
+/// Matches a list literal of values like `["eco", "away"]` or `[1, -2, 3]` with an optional trailing comma
+fn match_list(input: &str, options: ParseOptions) -> MatchResult<'_, Value> {
+    let (rest, items) = delimited(
+        trim(expect("value", token("["))),
+        terminated(
+            separated_list0(trim(token(",")), |input| match_list_item(input, options)),
+            opt(trim(token(","))),
+        ),
+        trim_front(token("]")),
+    )(input)?;
+
+    Ok((rest, Value::List(items)))
+}
+
+/// Matches a single value of a list literal, which may be negative (`-3` or `-"5min"`) or another list
+fn match_list_item(input: &str, options: ParseOptions) -> MatchResult<'_, Value> {
+    let negative = |input| {
+        let (rest, value) = preceded(trim(token("-")), |input| match_list_item(input, options))(input)?;
+        let negative = (-value).map_err(|_| nom::Err::Error(ParserError::expected(input, "value")))?;
+        Ok((rest, negative))
+    };
+    alt((
+        negative,
+        |input| match_value_type(input, options),
+        |input| match_list(input, options),
+    ))(input)
+}
+
 /// Remove whitespaces around
-fn trim<'a, F: 'a, O, E: ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+fn trim<'a, F, O, E: ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    F: Fn(&'a str) -> IResult<&'a str, O, E>,
+    F: Fn(&'a str) -> IResult<&'a str, O, E> + 'a,
 {
     delimited(multispace0, inner, multispace0)
 }
 
 /// Remove trailing whitespace
-fn trim_front<'a, F: 'a, O, E: ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+fn trim_front<'a, F, O, E: ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    F: Fn(&'a str) -> IResult<&'a str, O, E>,
+    F: Fn(&'a str) -> IResult<&'a str, O, E> + 'a,
 {
     preceded(multispace0, inner)
 }
 
-fn match_compare_op(input: &str) -> IResult<&str, Operator> {
+fn match_compare_op(input: &str) -> MatchResult<'_, Operator> {
     alt((
-        value(Operator::Equal, trim(tag("=="))),
-        value(Operator::NotEqual, trim(tag("!="))),
-        value(Operator::GreaterEqual, trim(tag(">="))),
-        value(Operator::LessEqual, trim(tag("<="))),
-        value(Operator::Greater, trim(tag(">"))),
-        value(Operator::Less, trim(tag("<"))),
+        value(Operator::Equal, trim(token("=="))),
+        value(Operator::Matches, trim(token("=~"))),
+        value(Operator::NotEqual, trim(token("!="))),
+        value(Operator::GreaterEqual, trim(token(">="))),
+        value(Operator::LessEqual, trim(token("<="))),
+        value(Operator::Greater, trim(token(">"))),
+        value(Operator::Less, trim(token("<"))),
+        value(Operator::In, trim(expect("in", match_keyword("in")))),
+        value(Operator::Contains, trim(expect("contains", match_keyword("contains")))),
+        value(
+            Operator::StartsWith,
+            trim(expect("starts_with", match_keyword("starts_with"))),
+        ),
+        value(
+            Operator::EndsWith,
+            trim(expect("ends_with", match_keyword("ends_with"))),
+        ),
+        value(
+            Operator::NotIn,
+            trim(expect("not in", |input| {
+                tuple((match_keyword("not"), multispace1, match_keyword("in")))(input)
+            })),
+        ),
     ))(input)
 }
 
-fn match_calc_op(input: &str) -> IResult<&str, Arithmetic> {
+fn match_calc_op(input: &str) -> MatchResult<'_, Arithmetic> {
     alt((
-        value(Arithmetic::Add, trim(tag("+"))),
-        value(Arithmetic::Sub, trim(tag("-"))),
-        value(Arithmetic::Mul, trim(tag("*"))),
-        value(Arithmetic::Div, trim(tag("/"))),
+        value(Arithmetic::Add, trim(token("+"))),
+        value(Arithmetic::Sub, trim(token("-"))),
+        value(Arithmetic::Pow, trim(token("**"))),
+        value(Arithmetic::Pow, trim(token("^"))),
+        value(Arithmetic::Mul, trim(token("*"))),
+        value(Arithmetic::IntDiv, trim(token("//"))),
+        value(Arithmetic::Div, trim(token("/"))),
+        value(Arithmetic::Rem, trim(token("%"))),
     ))(input)
 }
 
-fn match_varval_comparison(input: &str) -> IResult<&str, Comparison> {
-    let (rest, (identifier, calcs_lhs, op, value, calcs_rhs)) = tuple((
-        match_identifier,
-        match_calculations,
-        match_compare_op,
-        match_value_type,
-        match_calculations,
-    ))(input)?;
+/// Binding power of `arithmetic` where operators with a higher precedence are calculated first
+fn precedence(arithmetic: Arithmetic) -> u8 {
+    match arithmetic {
+        Arithmetic::Add | Arithmetic::Sub => 1,
+        Arithmetic::Mul | Arithmetic::Div | Arithmetic::Rem | Arithmetic::IntDiv => 2,
+        Arithmetic::Pow => 3,
+    }
+}
 
-    Ok((
-        rest,
-        Comparison {
-            what: ComparisonType::Variable(identifier.into(), calcs_lhs),
-            operator: op,
-            against: ComparisonType::Value(value, calcs_rhs),
-        },
-    ))
+/// Matches a single operand of a calculation: a value, a variable, a parenthesised calculation or a negation of those
+fn match_factor(input: &str, options: ParseOptions) -> MatchResult<'_, ComparisonType> {
+    alt((
+        // Exponentiation binds tighter than a negation (`-2 ** 2 == -4`)
+        map(
+            preceded(trim(token("-")), |input| {
+                match_calculation(input, precedence(Arithmetic::Pow), options)
+            }),
+            |operand| match operand {
+                ComparisonType::Value(value) if value.is_number() => match -value.clone() {
+                    Ok(negative) => ComparisonType::Value(negative),
+                    Err(_) => ComparisonType::Negative(Box::new(ComparisonType::Value(value))),
+                },
+                operand => ComparisonType::Negative(Box::new(operand)),
+            },
+        ),
+        map(|input| match_value_type(input, options), ComparisonType::Value),
+        map(|input| match_list(input, options), ComparisonType::Value),
+        map(match_exists, ComparisonType::Exists),
+        map(|input| match_call(input, options), ComparisonType::Call),
+        map(match_identifier, |name| ComparisonType::Variable(name.into())),
+        delimited(
+            trim(token("(")),
+            |input| match_operand(input, options),
+            trim_front(token(")")),
+        ),
+    ))(input)
 }
 
-fn match_varvar_comparison(input: &str) -> IResult<&str, Comparison> {
-    let (rest, (lhs, calcs_lhs, op, rhs, calcs_rhs)) = tuple((
-        match_identifier,
-        match_calculations,
-        match_compare_op,
-        match_identifier,
-        match_calculations,
-    ))(input)?;
+/// Matches `exists(name)`
+fn match_exists(input: &str) -> MatchResult<'_, String> {
+    let (rest, name) = preceded(
+        match_keyword("exists"),
+        delimited(trim(token("(")), match_identifier, trim_front(token(")"))),
+    )(input)?;
+
+    Ok((rest, name.into()))
+}
+
+/// Matches a function call like `abs(a - b)` or `max(a, b, 3)`
+fn match_call(input: &str, options: ParseOptions) -> MatchResult<'_, Call> {
+    let (rest, (name, arguments)) = pair(
+        terminated(match_identifier, pair(token("("), multispace0)),
+        // Anything after `name(` must be the arguments, it is not a variable followed by a block
+        cut(terminated(
+            separated_list0(trim(token(",")), |input| match_operand(input, options)),
+            trim_front(token(")")),
+        )),
+    )(input)?;
 
     Ok((
         rest,
-        Comparison {
-            what: ComparisonType::Variable(lhs.into(), calcs_lhs),
-            operator: op,
-            against: ComparisonType::Variable(rhs.into(), calcs_rhs),
+        Call {
+            name: name.into(),
+            arguments,
         },
     ))
 }
 
-fn match_value_calculation(input: &str) -> IResult<&str, Calculation> {
-    let (rest, (arithmetic, value)) = tuple((match_calc_op, match_value_type))(input)?;
+/// Matches a chain of calculations where all operators bind at least with `min_precedence`
+fn match_calculation(input: &str, min_precedence: u8, options: ParseOptions) -> MatchResult<'_, ComparisonType> {
+    let (mut rest, mut lhs) = match_factor(input, options)?;
 
-    let calculation = Calculation::Value(value, arithmetic);
+    // Try to acquire any appended arithmetic to the operand (e.G. a == foo + 2)
+    while let Ok((after_op, arithmetic)) = match_calc_op(rest) {
+        if precedence(arithmetic) < min_precedence {
+            break;
+        }
+        // Exponentiation is right-associative (`2 ** 3 ** 2 == 2 ** 9`), everything else left-associative
+        let min_precedence = match arithmetic {
+            Arithmetic::Pow => precedence(arithmetic),
+            _ => precedence(arithmetic) + 1,
+        };
+        let (new_rest, rhs) = match_calculation(after_op, min_precedence, options)?;
+
+        lhs = lhs.with_calculation(arithmetic, rhs);
+        rest = new_rest;
+    }
 
-    Ok((rest, calculation))
+    Ok((rest, lhs))
 }
 
-fn match_variable_calculation(input: &str) -> IResult<&str, Calculation> {
-    let (rest, (arithmetic, name)) = tuple((match_calc_op, match_identifier))(input)?;
+/// Matches one side of a comparison, which is a value, a variable or a calculation with the usual precedence
+/// of `*` and `/` over `+` and `-`. Any of those can fall back to another one with `??` which binds weakest.
+fn match_operand(input: &str, options: ParseOptions) -> MatchResult<'_, ComparisonType> {
+    let (mut rest, mut operand) = match_calculation(input, 0, options)?;
+
+    // Try to acquire any fallback for the operand (e.G. battery.level ?? 100)
+    while let Ok((after_op, _)) = trim(token("??"))(rest) {
+        let (new_rest, fallback) = match_calculation(after_op, 0, options)?;
 
-    let calculation = Calculation::Variable(name.into(), arithmetic);
+        operand = operand.with_fallback(fallback);
+        rest = new_rest;
+    }
 
-    Ok((rest, calculation))
+    Ok((rest, operand))
 }
 
-fn match_calculations(input: &str) -> IResult<&str, Vec<Calculation>> {
-    let mut rest = input;
-    let mut result = Vec::new();
-    // Try to acquire any appended arithmetic to the comparison (e.G. a == foo + 2)
-    while !rest.is_empty() {
-        let (_, arithmetic) = match_optional_calculation(rest)?;
-        if arithmetic.is_empty() {
-            break;
+fn match_comparison(input: &str, options: ParseOptions) -> MatchResult<'_, Comparison> {
+    let (rest, (what, operator)) = pair(|input| match_operand(input, options), match_compare_op)(input)?;
+    let (rest, against) = match operator {
+        // A string literal to match against is never guessed to be a duration or time (`name ends_with "1h"`)
+        Operator::Contains | Operator::StartsWith | Operator::EndsWith | Operator::Matches => {
+            match_operand(rest, ParseOptions { guess_literals: false })?
         }
-        let (new_rest, calculation) = match_value_calculation(rest).or_else(|_| match_variable_calculation(rest))?;
-
-        result.push(calculation);
-        rest = new_rest.trim();
-    }
+        _ => match_operand(rest, options)?,
+    };
 
-    Ok((rest, result))
+    Ok((
+        rest,
+        Comparison {
+            what,
+            operator,
+            against,
+        },
+    ))
 }
 
-fn match_comparison(input: &str) -> IResult<&str, Comparison> {
-    // This fails: alt((match_value_comparison, match_variable_comparison))(input)
-    let (rest, comparison) = match_varval_comparison(input).or_else(|_| match_varvar_comparison(input))?;
+/// Matches a range check like `now between "22:00:00" and "06:00:00"`
+fn match_between(input: &str, options: ParseOptions) -> MatchResult<'_, Between> {
+    let operand = |input| match_operand(input, options);
+    let (rest, (what, _, from, _, to)) = tuple((
+        operand,
+        trim(expect("between", match_keyword("between"))),
+        operand,
+        trim(expect("and", match_keyword("and"))),
+        operand,
+    ))(input)?;
 
-    Ok((rest, comparison))
+    Ok((rest, Between { what, from, to }))
+}
+
+/// Matches a [Comparison] or a [Between]
+fn match_condition(input: &str, options: ParseOptions) -> MatchResult<'_, Entity> {
+    alt((
+        map(|input| match_comparison(input, options), Entity::Comparison),
+        map(|input| match_between(input, options), Entity::Between),
+    ))(input)
 }
 
 /// Matches one underlying block with optional logic.
@@ -250,17 +546,25 @@ fn match_comparison(input: &str) -> IResult<&str, Comparison> {
 /// let (rest, (block, logic)) = match_block("&& (foo == 2 || foo == 3) ").unwrap();
 /// assert_eq!(block, "foo == 2 || foo == 3");
 /// ```
-pub fn match_block(input: &str) -> IResult<&str, (&str, Option<Logic>)> {
-    let (rest, (logics, block)) = tuple((
-        match_optional_logic,
-        delimited(trim(tag("(")), take_until_unbalanced('(', ')'), trim_front(tag(")"))),
-    ))(input)?;
+pub fn match_block(input: &str) -> MatchResult<'_, (&str, Option<Logic>)> {
+    let (rest, (logics, block)) = tuple((match_optional_logic, match_parenthesised))(input)?;
 
     let logic = decode_logic(logics);
 
     Ok((rest, (block, logic)))
 }
 
+/// Matches the content of a block in (balanced) parentheses
+fn match_parenthesised<'a>(input: &'a str) -> MatchResult<'a, &'a str> {
+    let content = |input: &'a str| {
+        let (_, content) = take_until_unbalanced('(', ')')(input)
+            .map_err(|error| error.map(|error| ParserError::from_error_kind(error.input, error.code)))?;
+        // The rest of an unclosed block is not a part of `input`, so take it from `input` for a valid offset
+        Ok((&input[content.len()..], content))
+    };
+    delimited(trim(token("(")), content, trim_front(token(")")))(input)
+}
+
 fn decode_logic(logics: Vec<&str>) -> Option<Logic> {
     (!logics.is_empty())
         .then(|| match logics[0] {
@@ -276,23 +580,37 @@ fn decode_logic(logics: Vec<&str>) -> Option<Logic> {
 /// Matches one comparison with optional logic.
 /// ```
 /// use metrics_evaluation::{
-///     calculate::{Arithmetic, Calculation},
-///     compare::{Comparison, Logic, Operator},
+///     calculate::Arithmetic,
+///     compare::{Comparison, ComparisonType, Logic, Operator},
 ///     expr_parser::match_comparisons,
 ///     value::Value,
 /// };
 ///
 /// let (rest, (cmp, logic)) = match_comparisons("hello > 1 + 2 - foo").unwrap();
-/// let mut expected = Comparison::from(("hello", Operator::Greater, Value::from(1)));
-/// expected
-///     .against
-///     .with_calculation(Calculation::Value(2.into(), Arithmetic::Add));
-/// expected
-///     .against
-///     .with_calculation(Calculation::Variable("foo".into(), Arithmetic::Sub));
+/// let expected = Comparison {
+///     what: ComparisonType::Variable("hello".into()),
+///     operator: Operator::Greater,
+///     against: ComparisonType::from(Value::from(1))
+///         .with_calculation(Arithmetic::Add, Value::from(2).into())
+///         .with_calculation(Arithmetic::Sub, ComparisonType::Variable("foo".into())),
+/// };
 /// assert_eq!(cmp, expected);
 /// assert_eq!(logic, None);
 ///
+/// // `*` and `/` are calculated before `+` and `-` unless parenthesised
+/// let (rest, (cmp, logic)) = match_comparisons("(a + 1) * 2 > 1 + b * 3").unwrap();
+/// let expected = Comparison {
+///     what: ComparisonType::Variable("a".into())
+///         .with_calculation(Arithmetic::Add, Value::from(1).into())
+///         .with_calculation(Arithmetic::Mul, Value::from(2).into()),
+///     operator: Operator::Greater,
+///     against: ComparisonType::from(Value::from(1)).with_calculation(
+///         Arithmetic::Add,
+///         ComparisonType::Variable("b".into()).with_calculation(Arithmetic::Mul, Value::from(3).into()),
+///     ),
+/// };
+/// assert_eq!(cmp, expected);
+///
 /// let (rest, (cmp, logic)) = match_comparisons("hello > 1 and foo < 2").unwrap();
 /// assert_eq!(
 ///     cmp,
@@ -324,8 +642,10 @@ fn decode_logic(logics: Vec<&str>) -> Option<Logic> {
 /// assert_eq!(cmp, Comparison::from(("bar", Operator::Equal, "foo")));
 /// assert_eq!(logic, Some(Logic::Or));
 /// ```
-pub fn match_comparisons(input: &str) -> IResult<&str, (Comparison, Option<Logic>)> {
-    let (rest, (logics, comparison)) = tuple((match_optional_logic, match_comparison))(input)?;
+pub fn match_comparisons(input: &str) -> MatchResult<'_, (Comparison, Option<Logic>)> {
+    let (rest, (logics, comparison)) = tuple((match_optional_logic, |input| {
+        match_comparison(input, ParseOptions::default())
+    }))(input)?;
 
     let logic = decode_logic(logics);
 
@@ -335,34 +655,255 @@ pub fn match_comparisons(input: &str) -> IResult<&str, (Comparison, Option<Logic
 /// Parse `input` recursively and produce a [Sequence] from all children.
 /// This [Sequence] can be thrown against a [crate::solver::solve_tree] using a [crate::resolver::Resolver] to solve the `input`.
 /// Use [crate::evaluate] to have an already implemented combination.
+///
+/// Like in rust, `&&` binds tighter than `||`, so `a || b && c` is parsed as `a || (b && c)`.
+/// A leading `!` or `not` negates the following comparison or block (`!(a > 1 && b == 2)`).
+/// A variable, value or calculation without a comparison is a predicate which must solve to [crate::Value::Bool].
+/// `a between b and c` checks a range including both ends, see [crate::Value::between].
 /// ```
-/// use metrics_evaluation::*;
+/// use metrics_evaluation::{compare::Logic, sequence::Entity, *};
 ///
 /// // Test for weird whitespaces
 /// assert!(parse_tree("hello > 1 && ( foo == 2 || foo == 3)").is_ok());
 /// assert!(parse_tree("hello > 1 && (foo == 2 || foo == 3 )").is_ok());
 /// assert!(parse_tree("hello > 1 && (foo == 2 || foo == 3 ) ").is_ok());
+///
+/// // Precedence of `&&` over `||`
+/// let sequence = parse_tree("a == 1 || b == 2 && c == 3").unwrap();
+/// assert_eq!(sequence.logic, Logic::Or);
+/// assert!(matches!(sequence.items[0], Entity::Comparison(_)));
+/// assert!(matches!(&sequence.items[1], Entity::Child(child) if child.logic == Logic::And && child.items.len() == 2));
+///
+/// // Negations
+/// let sequence = parse_tree("!(a == 1 || b == 2) && not c == 3").unwrap();
+/// assert!(matches!(&sequence.items[0], Entity::Not(child) if matches!(**child, Entity::Child(_))));
+/// assert!(matches!(&sequence.items[1], Entity::Not(child) if matches!(**child, Entity::Comparison(_))));
+/// assert!(matches!(&parse_tree("notify == 1").unwrap().items[0], Entity::Comparison(_)));
+///
+/// // Predicates
+/// let sequence = parse_tree("door.open && !true").unwrap();
+/// assert!(matches!(sequence.items[0], Entity::Predicate(_)));
+/// assert!(matches!(&sequence.items[1], Entity::Not(child) if matches!(**child, Entity::Predicate(_))));
+///
+/// // Ranges
+/// let sequence = parse_tree(r#"now between "22:00:00" and "06:00:00" and a between 1 and b + 2"#).unwrap();
+/// assert_eq!(sequence.logic, Logic::And);
+/// assert!(matches!(&sequence.items[..], [Entity::Between(_), Entity::Between(_)]));
+///
+/// // Logics must be placed between entities
+/// assert_eq!(
+///     parse_tree("a == 1 b == 2"),
+///     Err(Error::Syntax {
+///         offset: 7,
+///         line: 1,
+///         column: 8,
+///         expected: vec!["&&", "||"]
+///     })
+/// );
+/// // Errors report the furthest position the parser got to, also within nested blocks
+/// assert!(matches!(parse_tree("a == 1 && (b == 2 || c >)"), Err(Error::Syntax { offset: 24, .. })));
+/// assert!(matches!(parse_tree("a == 1 &&\n   b <"), Err(Error::Syntax { line: 2, column: 7, .. })));
+/// assert!(matches!(parse_tree("&& a == 1"), Err(Error::Syntax { offset: 0, .. })));
+/// assert!(matches!(parse_tree("a == 1 && > 2"), Err(Error::Syntax { offset: 10, .. })));
+/// assert!(matches!(parse_tree("a == 1 && (b > 1"), Err(Error::Syntax { offset: 16, .. })));
 /// ```
 pub fn parse_tree(input: impl AsRef<str>) -> Result<Sequence> {
-    let mut rest: &str = input.as_ref();
-    let mut results: Sequence = Sequence::default();
+    parse_tree_with_functions(input, &FunctionRegistry::default())
+}
+
+/// Parse `input` like [parse_tree] does, but allow calls of the custom `functions` besides the
+/// [crate::function::Builtin]s. Calls of unknown functions fail with [Error::UnknownFunction] and calls with a wrong
+/// number of arguments with [Error::WrongArity].
+pub fn parse_tree_with_functions(input: impl AsRef<str>, functions: &impl Signatures) -> Result<Sequence> {
+    parse_tree_with_options(input, ParseOptions::default(), functions)
+}
+
+/// Options of [parse_tree_with_options]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParseOptions {
+    /// Whether a string literal which reads like a duration, time, date or date-time (`"5min"`, `"08:00:00"`) is
+    /// guessed to be one. Without guessing, string literals are always [Value::String]s and other types need a typed
+    /// literal (`d"5min"`, `t"08:00:00"`) or a conversion (`date("2026-12-24")`). Defaults to `true`.
+    pub guess_literals: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self { guess_literals: true }
+    }
+}
+
+/// Parse `input` like [parse_tree_with_functions] does with the given `options`
+/// ```
+/// use metrics_evaluation::{expr_parser::*, function::FunctionRegistry, sequence::Entity, *};
+///
+/// let no_guessing = ParseOptions { guess_literals: false };
+/// let sequence = parse_tree_with_options(r#"device == "1m""#, no_guessing, &FunctionRegistry::default()).unwrap();
+/// match &sequence.items[..] {
+///     [Entity::Comparison(comparison)] => assert_eq!(comparison.against, Value::from("1m").into()),
+///     _ => unreachable!(),
+/// }
+///
+/// // Typed literals are never guessed
+/// let resolver = MapResolver::from(std::collections::HashMap::from([("device", "1m")]));
+/// assert_eq!(evaluate(r#"device == "1m""#, &resolver), Err(Error::IncompatibleTypes { op: "==".into(), lhs: "String", rhs: "Duration" }));
+/// assert_eq!(evaluate(r#"device == s"1m" && d"1m" == "60s" && t"08:00:00" < "09:00:00""#, &resolver), Ok(true));
+/// assert!(matches!(parse_tree(r#"a > d"soon""#), Err(Error::Syntax { offset: 5, .. })));
+/// ```
+pub fn parse_tree_with_options(
+    input: impl AsRef<str>,
+    options: ParseOptions,
+    functions: &impl Signatures,
+) -> Result<Sequence> {
+    let input = input.as_ref();
+    parse_sequence(input, input, options, functions)
+}
+
+/// Parse `input`, which is a part of `source`, so that all errors report their position relative to `source`.
+fn parse_sequence(source: &str, input: &str, options: ParseOptions, functions: &impl Signatures) -> Result<Sequence> {
+    let mut rest: &str = input;
+    // `||`-separated groups of `&&`-combined entities
+    let mut groups: Vec<Vec<Entity>> = Vec::new();
+    // The failure that made it furthest into `source` which is reported if parsing fails at all
+    let mut furthest: Option<ParserError> = None;
+    let fail = |furthest: Option<ParserError>, error: ParserError| {
+        let error = match furthest {
+            Some(furthest) => furthest.or(error),
+            None => error,
+        };
+        Error::syntax(source, source.offset(error.input), error.expected)
+    };
 
     while !rest.is_empty() {
-        let (block, comparison) = (match_block(rest), match_comparisons(rest));
-        match (block, comparison) {
-            (Ok((new_rest, (block, logic))), Err(_)) => {
-                rest = new_rest.trim();
-                results.items.push(Entity::Child(parse_tree(block)?, logic));
+        let (item, (logics, negations)) = match pair(match_optional_logic, match_negations)(rest) {
+            Ok(matched) => matched,
+            Err(error) => return Err(fail(furthest, ParserError::from_nom(error, rest))),
+        };
+        let logic = decode_logic(logics);
+
+        // A comparison may start with a parenthesised calculation (e.G. `(a + b) / 2 > 5`) which would
+        // be a block as well, so comparisons take precedence over blocks. Anything else must be a predicate.
+        let (new_rest, mut entity, failure) = match (match_parenthesised(item), match_condition(item, options)) {
+            (_, Ok((new_rest, condition))) => (new_rest, condition, None),
+            (Ok((new_rest, block)), Err(error)) => (
+                new_rest,
+                Entity::Child(parse_sequence(source, block, options, functions)?),
+                Some(ParserError::from_nom(error, item)),
+            ),
+            (Err(block_error), Err(error)) => {
+                let failure = ParserError::from_nom(block_error, item).or(ParserError::from_nom(error, item));
+                match match_operand(item, options) {
+                    Ok((new_rest, operand)) => (new_rest, Entity::Predicate(operand), Some(failure)),
+                    Err(error) => return Err(fail(furthest, failure.or(ParserError::from_nom(error, item)))),
+                }
             }
-            (Err(_), Ok((new_rest, (comparison, logic)))) => {
-                rest = new_rest.trim();
-                results.items.push(Entity::Comparison(comparison, logic));
+        };
+        check_calls(&entity, functions)?;
+        if let Some(failure) = failure {
+            furthest = Some(match furthest.take() {
+                Some(furthest) => furthest.or(failure),
+                None => failure,
+            });
+        }
+
+        for _ in 0..negations {
+            entity = Entity::Not(Box::new(entity));
+        }
+        let entity = compile_patterns(entity)?;
+
+        match (groups.last_mut(), logic) {
+            (None, None) | (Some(_), Some(Logic::Or)) => groups.push(vec![entity]),
+            (Some(group), Some(Logic::And)) => group.push(entity),
+            (None, Some(_)) => {
+                return Err(fail(
+                    furthest,
+                    ParserError {
+                        input: rest,
+                        expected: EXPECTED_ENTITY.to_vec(),
+                    },
+                ));
             }
-            _ => {
-                return Err(anyhow!("Syntax error near '{rest}'"));
+            (Some(_), None) => {
+                return Err(fail(
+                    furthest,
+                    ParserError {
+                        input: rest,
+                        expected: EXPECTED_LOGIC.to_vec(),
+                    },
+                ));
             }
         }
+
+        rest = new_rest.trim();
+    }
+
+    Ok(build_sequence(groups))
+}
+
+/// Fails if `entity` calls an unknown function or a function with a wrong number of arguments.
+/// Blocks are checked on their own when they are parsed.
+fn check_calls(entity: &Entity, functions: &impl Signatures) -> Result<()> {
+    let check = &mut |call: &Call| call.check(functions);
+    match entity {
+        Entity::Comparison(comparison) => {
+            comparison.what.check_calls(check)?;
+            comparison.against.check_calls(check)
+        }
+        Entity::Between(between) => {
+            between.what.check_calls(check)?;
+            between.from.check_calls(check)?;
+            between.to.check_calls(check)
+        }
+        Entity::Predicate(operand) => operand.check_calls(check),
+        Entity::Not(entity) => check_calls(entity, functions),
+        Entity::Child(_) => Ok(()),
     }
+}
 
-    Ok(results)
+/// Compiles the patterns of regular expression matches (`a =~ "^v1\."`) within `entity` once, so they are not
+/// compiled again on every solving. Patterns which are not a single string literal are compiled on solving.
+fn compile_patterns(entity: Entity) -> Result<Entity> {
+    match entity {
+        Entity::Comparison(Comparison {
+            what,
+            operator: Operator::Matches,
+            against: ComparisonType::Value(Value::String(pattern)),
+        }) => Ok(Entity::Comparison(Comparison {
+            what,
+            operator: Operator::Matches,
+            against: ComparisonType::Pattern(Pattern::new(&pattern)?),
+        })),
+        Entity::Not(entity) => Ok(Entity::Not(Box::new(compile_patterns(*entity)?))),
+        Entity::Child(Sequence { logic, items }) => Ok(Entity::Child(Sequence {
+            logic,
+            items: items.into_iter().map(compile_patterns).collect::<Result<_>>()?,
+        })),
+        entity => Ok(entity),
+    }
+}
+
+/// Builds the [Sequence] for `||`-separated `groups` of `&&`-combined [Entity]s
+fn build_sequence(mut groups: Vec<Vec<Entity>>) -> Sequence {
+    if groups.len() <= 1 {
+        return Sequence {
+            logic: Logic::And,
+            items: groups.pop().unwrap_or_default(),
+        };
+    }
+
+    let items = groups
+        .into_iter()
+        .map(|mut group| match group.len() {
+            1 => group.remove(0),
+            _ => Entity::Child(Sequence {
+                logic: Logic::And,
+                items: group,
+            }),
+        })
+        .collect();
+
+    Sequence {
+        logic: Logic::Or,
+        items,
+    }
 }
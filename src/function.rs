@@ -0,0 +1,450 @@
+use std::{collections::HashMap, fmt::Display};
+
+use chrono::{Duration, Timelike};
+
+use crate::{
+    compare::{Compareable, ComparisonType, Operator},
+    error::{Error, Result},
+    resolver::Resolver,
+    value::Value,
+};
+
+/// A call of a function by its `name` on `arguments` which themselves can be any [ComparisonType]
+/// (e.G. `abs(a - b)`)
+#[derive(Debug, PartialEq)]
+pub struct Call {
+    /// Name of the called function
+    pub name: String,
+    /// Operands which are solved and passed to the function
+    pub arguments: Vec<ComparisonType>,
+}
+
+impl Call {
+    /// The [Builtin] which is called or [Error::UnknownFunction] if there is none called like this
+    pub fn builtin(&self) -> Result<Builtin> {
+        Builtin::from_name(&self.name).ok_or_else(|| Error::UnknownFunction(self.name.clone()))
+    }
+
+    /// Fails with [Error::UnknownFunction] if neither `functions` nor the [Builtin]s know the called function,
+    /// or with [Error::WrongArity] if it is called with a wrong number of arguments
+    pub fn check(&self, functions: &impl Signatures) -> Result<()> {
+        match functions.arguments(&self.name) {
+            Some(types) => check_arity(&self.name, Arity::Exactly(types.len()), self.arguments.len()),
+            None => self.builtin()?.check_arity(self.arguments.len()),
+        }
+    }
+}
+
+impl Display for Call {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (index, argument) in self.arguments.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", argument)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Number of arguments a function takes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    /// Whether a function of this [Arity] can be called with `count` arguments
+    pub fn accepts(self, count: usize) -> bool {
+        match self {
+            Self::Exactly(arity) => count == arity,
+            Self::AtLeast(arity) => count >= arity,
+        }
+    }
+}
+
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exactly(arity) => write!(f, "{}", arity),
+            Self::AtLeast(arity) => write!(f, "at least {}", arity),
+        }
+    }
+}
+
+/// Functions which can be called in any expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Builtin {
+    /// `abs(number)` or `abs(duration)`
+    Abs,
+    /// `min(a, b, ...)` or `min(list)`, the least of values which can be ordered
+    Min,
+    /// `max(a, b, ...)` or `max(list)`, the greatest of values which can be ordered
+    Max,
+    /// `round(number)` to the nearest integer, half-way cases away from zero
+    Round,
+    /// `floor(number)`, the largest integer less than or equal to the number
+    Floor,
+    /// `ceil(number)`, the smallest integer greater than or equal to the number
+    Ceil,
+    /// `len(string)` in characters or `len(list)` in items
+    Len,
+    /// `lower(string)` in lowercase
+    Lower,
+    /// `hour(time)` or `hour(date_time)` at its own offset
+    Hour,
+    /// `duration(string)` in [humantime] format, which is never guessed to be of another type
+    Duration,
+    /// `time(string)` in the form of `%H:%M:%S`
+    Time,
+    /// `date(string)` in the form of `%Y-%m-%d`
+    Date,
+    /// `datetime(string)` in RFC 3339 format, which is UTC if the offset is missing
+    DateTime,
+}
+
+impl Builtin {
+    /// The [Builtin] called `name` or [None] if there is none
+    pub fn from_name(name: &str) -> Option<Self> {
+        let builtin = match name {
+            "abs" => Self::Abs,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "round" => Self::Round,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            "len" => Self::Len,
+            "lower" => Self::Lower,
+            "hour" => Self::Hour,
+            "duration" => Self::Duration,
+            "time" => Self::Time,
+            "date" => Self::Date,
+            "datetime" => Self::DateTime,
+            _ => return None,
+        };
+        Some(builtin)
+    }
+
+    /// Name of the [Builtin] as it is called in an expression
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Abs => "abs",
+            Self::Min => "min",
+            Self::Max => "max",
+            Self::Round => "round",
+            Self::Floor => "floor",
+            Self::Ceil => "ceil",
+            Self::Len => "len",
+            Self::Lower => "lower",
+            Self::Hour => "hour",
+            Self::Duration => "duration",
+            Self::Time => "time",
+            Self::Date => "date",
+            Self::DateTime => "datetime",
+        }
+    }
+
+    /// Name of the [Value] type a conversion [Builtin] like [Builtin::Duration] results in
+    fn type_name(self) -> &'static str {
+        match self {
+            Self::Duration => "Duration",
+            Self::Time => "Time",
+            Self::Date => "Date",
+            _ => "DateTime",
+        }
+    }
+
+    pub fn arity(self) -> Arity {
+        match self {
+            Self::Min | Self::Max => Arity::AtLeast(1),
+            _ => Arity::Exactly(1),
+        }
+    }
+
+    /// Fails with [Error::WrongArity] if the [Builtin] can not be called with `count` arguments
+    pub fn check_arity(self, count: usize) -> Result<()> {
+        check_arity(self.name(), self.arity(), count)
+    }
+
+    /// Call the [Builtin] on `arguments` which must match its [Builtin::arity].
+    /// Any [Value::Null] argument makes the result [Value::Null].
+    /// ```
+    /// use metrics_evaluation::{function::Builtin, *};
+    ///
+    /// assert_eq!(Builtin::Abs.call(vec![Value::Integer(-3)]), Ok(Value::Integer(3)));
+    /// assert_eq!(
+    ///     Builtin::Max.call(vec![Value::Integer(1), Value::Numeric(2.5), Value::Integer(2)]),
+    ///     Ok(Value::Numeric(2.5))
+    /// );
+    /// assert_eq!(Builtin::Min.call(vec![Value::from(vec![3, 1, 2])]), Ok(Value::Integer(1)));
+    /// assert_eq!(Builtin::Len.call(vec![Value::from("Grüße")]), Ok(Value::Integer(5)));
+    /// assert_eq!(Builtin::Round.call(vec![Value::Null]), Ok(Value::Null));
+    /// assert!(Builtin::Lower.call(vec![Value::Integer(1)]).is_err());
+    /// assert_eq!(Builtin::Time.call(vec![Value::from("12:00:00")]), Ok(Value::from(chrono::NaiveTime::from_hms(12, 0, 0))));
+    /// assert_eq!(
+    ///     Builtin::Duration.call(vec![Value::from("soon")]),
+    ///     Err(Error::InvalidConversion { to: "Duration", value: "soon".into() })
+    /// );
+    /// ```
+    pub fn call(self, arguments: Vec<Value>) -> Result<Value> {
+        self.check_arity(arguments.len())?;
+        if arguments.contains(&Value::Null) {
+            return Ok(Value::Null);
+        }
+
+        match self {
+            Self::Min => extreme(self, arguments, Operator::Less),
+            Self::Max => extreme(self, arguments, Operator::Greater),
+            _ => self.call_one(arguments.into_iter().next().unwrap_or(Value::Null)),
+        }
+    }
+
+    fn call_one(self, argument: Value) -> Result<Value> {
+        let result = match (self, argument) {
+            (Self::Abs, Value::Numeric(value)) => Value::Numeric(value.abs()),
+            (Self::Abs, Value::Integer(value)) => Value::from_integer((value as i128).abs())?,
+            (Self::Abs, Value::Duration(value)) if value < Duration::zero() => Value::Duration(-value),
+            (Self::Round, Value::Numeric(value)) => Value::Numeric(value.round()),
+            (Self::Floor, Value::Numeric(value)) => Value::Numeric(value.floor()),
+            (Self::Ceil, Value::Numeric(value)) => Value::Numeric(value.ceil()),
+            // Integers are integral already and other durations are not negative
+            (Self::Abs | Self::Round | Self::Floor | Self::Ceil, value @ (Value::Integer(_) | Value::Unsigned(_))) => {
+                value
+            }
+            (Self::Abs, value @ Value::Duration(_)) => value,
+            (Self::Len, Value::String(value)) => Value::Integer(value.chars().count() as i64),
+            (Self::Len, Value::List(items)) => Value::Integer(items.len() as i64),
+            (Self::Lower, Value::String(value)) => Value::String(value.to_lowercase()),
+            (Self::Hour, Value::Time(value)) => Value::Integer(value.hour().into()),
+            (Self::Hour, Value::DateTime(value)) => Value::Integer(value.hour().into()),
+            // Values of the type converted to already stay as they are
+            (Self::Duration, value @ Value::Duration(_))
+            | (Self::Time, value @ Value::Time(_))
+            | (Self::Date, value @ Value::Date(_))
+            | (Self::DateTime, value @ Value::DateTime(_)) => value,
+            (Self::Duration | Self::Time | Self::Date | Self::DateTime, Value::String(text)) => {
+                let value = match self {
+                    Self::Duration => Value::parse_duration(&text),
+                    Self::Time => Value::parse_time(&text),
+                    Self::Date => Value::parse_date(&text),
+                    _ => Value::parse_date_time(&text),
+                };
+                value.ok_or(Error::InvalidConversion {
+                    to: self.type_name(),
+                    value: text,
+                })?
+            }
+            (_, value) => {
+                return Err(Error::IncompatibleType {
+                    op: self.name().into(),
+                    value: value.type_name(),
+                })
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+fn check_arity(function: &str, arity: Arity, count: usize) -> Result<()> {
+    if arity.accepts(count) {
+        return Ok(());
+    }
+    Err(Error::WrongArity {
+        function: function.into(),
+        expected: arity,
+        found: count,
+    })
+}
+
+/// The least ([Operator::Less]) or greatest ([Operator::Greater]) of all `arguments`, or of all items of a single
+/// [Value::List]. Is [Value::Null] for an empty list or a list containing [Value::Null].
+fn extreme(builtin: Builtin, mut arguments: Vec<Value>, operator: Operator) -> Result<Value> {
+    if let [Value::List(_)] = arguments.as_slice() {
+        if let Some(Value::List(items)) = arguments.pop() {
+            arguments = items;
+        }
+    }
+    if arguments.contains(&Value::Null) {
+        return Ok(Value::Null);
+    }
+
+    let mut arguments = arguments.into_iter();
+    let mut result = match arguments.next() {
+        Some(first) => first,
+        None => return Ok(Value::Null),
+    };
+    for argument in arguments {
+        let is_extreme = argument
+            .compare(&result, operator)
+            .map_err(|_| Error::IncompatibleTypes {
+                op: builtin.name().into(),
+                lhs: result.type_name(),
+                rhs: argument.type_name(),
+            })?;
+        if is_extreme {
+            result = argument;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Type of [Value] a custom function takes as an argument
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgumentType {
+    /// Any value including [Value::Null]
+    Any,
+    /// [Value::Numeric], [Value::Integer] or [Value::Unsigned]
+    Number,
+    String,
+    Bool,
+    Time,
+    Date,
+    DateTime,
+    Duration,
+    List,
+}
+
+impl ArgumentType {
+    /// Whether `value` can be passed as an argument of this [ArgumentType]
+    pub fn accepts(self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (Self::Any, _)
+                | (Self::Number, Value::Numeric(_) | Value::Integer(_) | Value::Unsigned(_))
+                | (Self::String, Value::String(_))
+                | (Self::Bool, Value::Bool(_))
+                | (Self::Time, Value::Time(_))
+                | (Self::Date, Value::Date(_))
+                | (Self::DateTime, Value::DateTime(_))
+                | (Self::Duration, Value::Duration(_))
+                | (Self::List, Value::List(_))
+        )
+    }
+}
+
+/// Declares the argument types of custom functions so calls can be checked on parsing
+/// (see [crate::expr_parser::parse_tree_with_functions])
+pub trait Signatures {
+    /// Types of the arguments of the custom function `name` or [None] if there is no such function
+    fn arguments(&self, name: &str) -> Option<&[ArgumentType]>;
+}
+
+/// Checks `arguments` of a call of the custom function `name` against its argument `types`.
+/// Is `false` if the function must not be called because a typed argument is [Value::Null], which makes the result
+/// [Value::Null] just like for [Builtin]s.
+pub(crate) fn check_arguments(name: &str, types: &[ArgumentType], arguments: &[Value]) -> Result<bool> {
+    check_arity(name, Arity::Exactly(types.len()), arguments.len())?;
+    let mut callable = true;
+    for (argument_type, argument) in types.iter().zip(arguments) {
+        match argument {
+            Value::Null if *argument_type != ArgumentType::Any => callable = false,
+            argument if !argument_type.accepts(argument) => {
+                return Err(Error::IncompatibleType {
+                    op: name.into(),
+                    value: argument.type_name(),
+                })
+            }
+            _ => {}
+        }
+    }
+    Ok(callable)
+}
+
+type Function = Box<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>;
+
+/// Custom functions which can be called in expressions besides the [Builtin]s, shadowing a [Builtin] of the same
+/// name. Pass it alongside a [Resolver] with [WithFunctions], [crate::evaluate_with_functions] or
+/// [crate::solve_tree_with_functions].
+/// ```
+/// use metrics_evaluation::{function::*, *};
+///
+/// let mut functions = FunctionRegistry::default();
+/// functions.register("clamp", [ArgumentType::Number, ArgumentType::Number, ArgumentType::Number], |arguments| {
+///     let (value, low, high) = (arguments[0].clone(), arguments[1].clone(), arguments[2].clone());
+///     Builtin::Min.call(vec![Builtin::Max.call(vec![value, low])?, high])
+/// });
+///
+/// let resolver = MapResolver::from(std::collections::HashMap::from([("a", 150)]));
+/// assert_eq!(evaluate_with_functions("clamp(a, 0, 100) == 100", &resolver, &functions), Ok(true));
+/// assert_eq!(
+///     evaluate_with_functions("clamp(a, 0) == 100", &resolver, &functions),
+///     Err(Error::WrongArity { function: "clamp".into(), expected: Arity::Exactly(3), found: 2 })
+/// );
+/// assert_eq!(
+///     evaluate_with_functions("clamp(a, 0, \"100\") == 100", &resolver, &functions),
+///     Err(Error::IncompatibleType { op: "clamp".into(), value: "String" })
+/// );
+/// assert_eq!(
+///     evaluate_with_functions("wrap(a) == 100", &resolver, &functions),
+///     Err(Error::UnknownFunction("wrap".into()))
+/// );
+/// ```
+#[derive(Default)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, (Vec<ArgumentType>, Function)>,
+}
+
+impl FunctionRegistry {
+    /// Registers `function` as `name` taking arguments of the given `types`, replacing any function registered with
+    /// that name before. `function` is only called with arguments matching `types`.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        types: impl Into<Vec<ArgumentType>>,
+        function: impl Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.functions.insert(name.into(), (types.into(), Box::new(function)));
+        self
+    }
+
+    /// Calls the function `name` on `arguments` or is [None] if there is no such function
+    pub fn call(&self, name: &str, arguments: &[Value]) -> Option<Result<Value>> {
+        let (types, function) = self.functions.get(name)?;
+        Some(match check_arguments(name, types, arguments) {
+            Ok(true) => function(arguments),
+            Ok(false) => Ok(Value::Null),
+            Err(error) => Err(error),
+        })
+    }
+}
+
+impl Signatures for FunctionRegistry {
+    fn arguments(&self, name: &str) -> Option<&[ArgumentType]> {
+        self.functions.get(name).map(|(types, _)| types.as_slice())
+    }
+}
+
+impl std::fmt::Debug for FunctionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.functions.iter().map(|(name, (types, _))| (name, types)))
+            .finish()
+    }
+}
+
+/// A [Resolver] which resolves variables with `resolver` and calls the custom `functions`
+#[derive(Debug)]
+pub struct WithFunctions<'a, R, F> {
+    pub resolver: &'a R,
+    pub functions: &'a F,
+}
+
+impl<'a, R, F> WithFunctions<'a, R, F> {
+    pub fn new(resolver: &'a R, functions: &'a F) -> Self {
+        Self { resolver, functions }
+    }
+}
+
+impl<R: Resolver> Resolver for WithFunctions<'_, R, FunctionRegistry> {
+    fn resolve(&self, name: impl AsRef<str>) -> Option<&Value> {
+        self.resolver.resolve(name)
+    }
+
+    fn call(&self, name: &str, arguments: &[Value]) -> Option<Result<Value>> {
+        self.functions.call(name, arguments)
+    }
+}
@@ -5,14 +5,24 @@
 //!
 //! ```foo + 2 > 2 && bar != 42 || (baz == 47111 && barg * 42 <= 99) && foo >= bar - 5```.
 //!
-//! The only limitation at the moment is that you currently have to use a variable-name on the left-hand (WIP). Left-Hand values are not supported yet.
+//! Both sides of a comparison can be a value, a variable or a calculation, so `100 > foo` is as valid as `foo < 100`.
 //!
 //! Comparisons can be made against any [Value]-Type implemented:
 //! - [Value::String] encapsulated in quotation marks
-//! - [Value::Numeric] (internally [f64] but everything from u8 to f64 will be converted Into it automatically)
+//! - [Value::Numeric] (internally [f64] but everything from u8 to u32, f32 and f64 will be converted Into it automatically)
+//! - [Value::Integer] (internally [i64], which [i64] and [u64] up to [i64::MAX] are converted Into) for exact integers
+//! - [Value::Unsigned] (internally [u64]) for exact integers above [i64::MAX]
 //! - [Value::Bool] which is simply a [bool]
 //! - [Value::Time] (internally [chrono::NaiveTime]), encapsulated in quotation marks and expressed in form of "%H:%M:%S" as of NaiveTime::parse_from_str().
 //! - [Value::Duration] as [chrono::Duration] encapsulated in quotation marks and represented in [humantime::Duration] (see [humantime::parse_duration] for formatting possibilities) for ease of use
+//! - [Value::Date] and [Value::DateTime] encapsulated in quotation marks and written in ISO 8601 / RFC 3339
+//! - [Value::List] of other values written like `["eco", "away"]` to check for a value `in` or `not in` it
+//! - [Value::Null] written as `null` for an explicitly reported "no value"
+//!
+//! Quoted values are guessed to be a [Value::Duration], [Value::Time], [Value::Date] or [Value::DateTime] if they read
+//! like one. Typed literals (`d"5min"`, `t"08:00:00"`, `date"2026-12-24"`, `datetime"2026-12-24T18:00:00Z"`,
+//! `s"1m"`) are never guessed and [crate::parse_tree_with_options] or [crate::evaluate_with_options] (and the other
+//! `*_with_options` functions) can turn guessing off.
 //!
 //! Value-Lookup is made through a given [Resolver]-trait internally so you are open to use what ever you like in the background to resolve variable-names to their value-representation.
 //!
@@ -20,36 +30,60 @@
 //!
 //! For laziness there is a [MapResolver] which implements [Resolver] and can be made [From] any [std::collections::HashMap] that contain a Key which is [AsRef]\<str> and values which can be made [Value]::[From].
 //!
+//! Besides the built-in functions (see [function::Builtin]), custom functions can be registered with their argument
+//! types in a [function::FunctionRegistry] and called through [crate::evaluate_with_functions] or
+//! [crate::solve_tree_with_functions].
+//!
 //! For the ease of use, an `crate::evaluate` function is implemented which just takes a string and compares using a given resolver.
 //!
+//! All fallible functions return a [Result] with a typed [Error], so a syntax error ([Error::Syntax]) can be told apart
+//! from a missing variable ([Error::UnresolvedVariable]) or a calculation on incompatible types ([Error::IncompatibleTypes]).
+//!
+//! With [crate::evaluate_tristate] and [crate::solve_tree_tristate], unresolvable variables do not fail but make a
+//! comparison [Tristate::Unknown] with three-valued (Kleene) logic, so a decided expression stays decided.
+//!
+//! To find out why an expression solved to its result, [crate::explain()] and [crate::explain_tree] return an [Explanation]
+//! with all resolved values and calculation steps which renders as human-readable text.
+//!
 //! To have a more performant usage of this crate, use [crate::parse_tree] which produces a pre-parsed [Sequence] once.
 //! This [Sequence] can then be used in subsequent calls to [crate::solve_tree] to evalaute the [Sequence] with current variable-values over and over again.
 
-use anyhow::Result;
-
 /// Compute arithmetics on [Value]s
 pub mod calculate;
 /// Compare [Value] against [Value]
 pub mod compare;
+/// Errors of parsing and solving
+pub mod error;
+/// Traces of solving a [Sequence]
+pub mod explain;
 /// Parser to generate [Sequence] from a given text
 pub mod expr_parser;
+/// Functions which can be called in expressions
+pub mod function;
 /// Helper-Object to use [std::collections::HashMap] as [Resolver]
 pub mod mapresolver;
+/// How the solvers record what they solved
+mod record;
 /// Resolves name to [Value]
 pub mod resolver;
 /// Sequence of comparisons
 pub mod sequence;
 /// Solves [Sequence]
 pub mod solver;
+/// Three-valued results of solving
+pub mod tristate;
 /// A generic value
 pub mod value;
 
 pub use calculate::{Arithmetic, Calculateable, Calculation};
-pub use expr_parser::parse_tree;
+pub use error::{Error, Result};
+pub use explain::Explanation;
+pub use expr_parser::{parse_tree, parse_tree_with_functions, parse_tree_with_options, ParseOptions};
 pub use mapresolver::MapResolver;
 pub use resolver::Resolver;
 pub use sequence::Sequence;
-pub use solver::solve_tree;
+pub use solver::{explain_tree, solve_tree, solve_tree_tristate, solve_tree_with_functions};
+pub use tristate::Tristate;
 pub use value::Value;
 
 /// Evaluate string-`sequence` with the given [Resolver] resolver to a final bool-result.
@@ -57,15 +91,80 @@ pub use value::Value;
 /// Use this if the input-sequence is changing on the same logic. To have a better performing solution where
 /// input-sequences do not change and where you just want to check a given logic against changing metrics, save the
 /// output of [parse_tree] and throw it towards a value-changing [Resolver] in a [solve_tree] when needed.
-pub fn evaluate<'a>(sequence: impl AsRef<str>, resolver: &'a impl resolver::Resolver) -> Result<bool> {
+pub fn evaluate(sequence: impl AsRef<str>, resolver: &impl resolver::Resolver) -> Result<bool> {
     let comparisons = expr_parser::parse_tree(sequence)?;
     solver::solve_tree(&comparisons, resolver)
 }
 
+/// Evaluate string-`sequence` like [evaluate] does but with three-valued logic where unresolvable variables
+/// are [Tristate::Unknown] instead of an [Error::UnresolvedVariable] (see [solve_tree_tristate]).
+pub fn evaluate_tristate(sequence: impl AsRef<str>, resolver: &impl resolver::Resolver) -> Result<Tristate> {
+    evaluate_tristate_with_options(sequence, resolver, ParseOptions::default())
+}
+
+/// Evaluate string-`sequence` like [evaluate] does but return an [Explanation] of how the result came to be.
+pub fn explain(sequence: impl AsRef<str>, resolver: &impl resolver::Resolver) -> Result<Explanation> {
+    explain_with_options(sequence, resolver, ParseOptions::default())
+}
+
+/// Evaluate string-`sequence` like [evaluate] does but with the custom `functions` callable besides the
+/// [function::Builtin]s. Calls of functions which are neither are rejected on parsing.
+pub fn evaluate_with_functions(
+    sequence: impl AsRef<str>,
+    resolver: &impl resolver::Resolver,
+    functions: &function::FunctionRegistry,
+) -> Result<bool> {
+    evaluate_with_options(sequence, resolver, ParseOptions::default(), functions)
+}
+
+/// Evaluate string-`sequence` like [evaluate_with_functions] does but parse it with the given `options`
+/// (see [parse_tree_with_options]).
+/// ```
+/// use metrics_evaluation::{expr_parser::ParseOptions, function::FunctionRegistry, *};
+///
+/// let resolver = MapResolver::from(std::collections::HashMap::from([("device", "1m")]));
+/// let no_guessing = ParseOptions { guess_literals: false };
+/// assert!(evaluate(r#"device == "1m""#, &resolver).is_err());
+/// assert_eq!(evaluate_with_options(r#"device == "1m""#, &resolver, no_guessing, &FunctionRegistry::default()), Ok(true));
+/// assert_eq!(evaluate_tristate_with_options(r#"device == "1m""#, &resolver, no_guessing), Ok(Tristate::True));
+/// assert_eq!(explain_with_options(r#"device == "1m""#, &resolver, no_guessing).map(|e| e.result()), Ok(Some(true)));
+/// ```
+pub fn evaluate_with_options(
+    sequence: impl AsRef<str>,
+    resolver: &impl resolver::Resolver,
+    options: ParseOptions,
+    functions: &function::FunctionRegistry,
+) -> Result<bool> {
+    let comparisons = expr_parser::parse_tree_with_options(sequence, options, functions)?;
+    solver::solve_tree_with_functions(&comparisons, resolver, functions)
+}
+
+/// Evaluate string-`sequence` like [evaluate_tristate] does but parse it with the given `options`
+pub fn evaluate_tristate_with_options(
+    sequence: impl AsRef<str>,
+    resolver: &impl resolver::Resolver,
+    options: ParseOptions,
+) -> Result<Tristate> {
+    let comparisons = expr_parser::parse_tree_with_options(sequence, options, &function::FunctionRegistry::default())?;
+    solver::solve_tree_tristate(&comparisons, resolver)
+}
+
+/// Evaluate string-`sequence` like [explain()] does but parse it with the given `options`
+pub fn explain_with_options(
+    sequence: impl AsRef<str>,
+    resolver: &impl resolver::Resolver,
+    options: ParseOptions,
+) -> Result<Explanation> {
+    let comparisons = expr_parser::parse_tree_with_options(sequence, options, &function::FunctionRegistry::default())?;
+    solver::explain_tree(&comparisons, resolver)
+}
+
 /// A serde deserializer for [Sequence]
 #[cfg(feature = "serde_de")]
 pub mod serde_de;
 
+#[cfg(feature = "async")]
+pub mod async_function;
 #[cfg(feature = "async")]
 pub mod async_resolver;
 #[cfg(feature = "async")]
@@ -75,10 +174,69 @@ pub use async_resolver::AsyncResolver;
 
 #[cfg(feature = "async")]
 /// Async-version of 'evaluate'
-pub async fn evaluate_async<'a>(
-    sequence: impl AsRef<str>,
-    resolver: &'a impl async_resolver::AsyncResolver,
-) -> Result<bool> {
+pub async fn evaluate_async(sequence: impl AsRef<str>, resolver: &impl async_resolver::AsyncResolver) -> Result<bool> {
     let comparisons = expr_parser::parse_tree(sequence)?;
     async_solver::solve_tree(&comparisons, resolver).await
 }
+
+#[cfg(feature = "async")]
+/// Async-version of 'evaluate_with_functions'
+pub async fn evaluate_with_functions_async(
+    sequence: impl AsRef<str>,
+    resolver: &impl async_resolver::AsyncResolver,
+    functions: &async_function::AsyncFunctionRegistry,
+) -> Result<bool> {
+    evaluate_with_options_async(sequence, resolver, ParseOptions::default(), functions).await
+}
+
+#[cfg(feature = "async")]
+/// Async-version of 'evaluate_tristate'
+pub async fn evaluate_tristate_async(
+    sequence: impl AsRef<str>,
+    resolver: &impl async_resolver::AsyncResolver,
+) -> Result<Tristate> {
+    evaluate_tristate_with_options_async(sequence, resolver, ParseOptions::default()).await
+}
+
+#[cfg(feature = "async")]
+/// Async-version of 'explain'
+pub async fn explain_async(
+    sequence: impl AsRef<str>,
+    resolver: &impl async_resolver::AsyncResolver,
+) -> Result<Explanation> {
+    explain_with_options_async(sequence, resolver, ParseOptions::default()).await
+}
+
+#[cfg(feature = "async")]
+/// Async-version of 'evaluate_with_options'
+pub async fn evaluate_with_options_async(
+    sequence: impl AsRef<str>,
+    resolver: &impl async_resolver::AsyncResolver,
+    options: ParseOptions,
+    functions: &async_function::AsyncFunctionRegistry,
+) -> Result<bool> {
+    let comparisons = expr_parser::parse_tree_with_options(sequence, options, functions)?;
+    async_solver::solve_tree_with_functions(&comparisons, resolver, functions).await
+}
+
+#[cfg(feature = "async")]
+/// Async-version of 'evaluate_tristate_with_options'
+pub async fn evaluate_tristate_with_options_async(
+    sequence: impl AsRef<str>,
+    resolver: &impl async_resolver::AsyncResolver,
+    options: ParseOptions,
+) -> Result<Tristate> {
+    let comparisons = expr_parser::parse_tree_with_options(sequence, options, &function::FunctionRegistry::default())?;
+    async_solver::solve_tree_tristate(&comparisons, resolver).await
+}
+
+#[cfg(feature = "async")]
+/// Async-version of 'explain_with_options'
+pub async fn explain_with_options_async(
+    sequence: impl AsRef<str>,
+    resolver: &impl async_resolver::AsyncResolver,
+    options: ParseOptions,
+) -> Result<Explanation> {
+    let comparisons = expr_parser::parse_tree_with_options(sequence, options, &function::FunctionRegistry::default())?;
+    async_solver::explain_tree(&comparisons, resolver).await
+}
@@ -14,27 +14,27 @@ where
     Value: From<V>,
 {
     fn from(map: HashMap<K, V>) -> Self {
-        Self {
-            0: map
-                .into_iter()
+        Self(
+            map.into_iter()
                 .map(|(variable_name, value)| (variable_name.as_ref().into(), Value::from(value)))
                 .collect(),
-        }
+        )
     }
 }
 
 impl Resolver for MapResolver {
     fn resolve(&self, name: impl AsRef<str>) -> Option<&Value> {
-        self.0.get(name.as_ref()).map(|value| value)
+        self.0.get(name.as_ref())
     }
 }
 
 /// This thing only makes sense in tests IMHO
 #[cfg(test)]
 #[cfg(feature = "async")]
-#[async_trait::async_trait]
+#[async_trait::async_trait(?Send)]
 impl crate::async_resolver::AsyncResolver for MapResolver {
+    #[allow(mismatched_lifetime_syntaxes)]
     async fn resolve<'a>(&self, name: impl AsRef<str> + Send + 'a) -> Option<&Value> {
-        self.0.get(name.as_ref()).map(|value| value)
+        self.0.get(name.as_ref())
     }
 }
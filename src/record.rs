@@ -0,0 +1,157 @@
+use crate::{
+    calculate::Arithmetic,
+    compare::{ComparisonType, Logic, Operator},
+    error::{Error, Result},
+    sequence::Entity,
+    tristate::Tristate,
+    value::Value,
+};
+
+/// What the solvers keep of a solved operand: the plain [Value] or a full [crate::explain::Operand]
+pub(crate) trait RecordOperand: Sized {
+    fn value(&self) -> &Value;
+    fn fixed(value: Value) -> Self;
+    fn variable(name: &str, value: Value) -> Self;
+    fn calculation(lhs: Self, arithmetic: Arithmetic, rhs: Self, value: Value) -> Self;
+    fn negative(operand: Self, value: Value) -> Self;
+    fn coalesce(expression: &ComparisonType, used: Self, missing: Option<&ComparisonType>) -> Self;
+    fn exists(name: &str, value: Value) -> Self;
+    fn call(name: &str, arguments: Vec<Self>, value: Value) -> Self;
+}
+
+/// What the solvers keep of the items of a [crate::Sequence] they solved. Solving, three-valued solving and
+/// explaining share one walk of the [crate::Sequence] which is generic over this: [Solve] only keeps the results,
+/// [SolveTristate] turns unresolvable variables into [Tristate::Unknown] and [crate::explain::Explain] traces
+/// everything into an [crate::Explanation].
+pub(crate) trait Record {
+    type Operand: RecordOperand;
+    type Entity;
+    /// The solved items of a [crate::Sequence]
+    type Items: Default;
+
+    fn result(entity: &Self::Entity) -> Tristate;
+    /// A comparison, range or predicate which failed with `error`
+    fn unsolved(error: Error) -> Result<Self::Entity> {
+        Err(error)
+    }
+    fn comparison(what: Self::Operand, operator: Operator, against: Self::Operand, result: bool) -> Self::Entity;
+    fn between(what: Self::Operand, from: Self::Operand, to: Self::Operand, result: bool) -> Self::Entity;
+    fn not(entity: Self::Entity) -> Self::Entity;
+    fn predicate(operand: Self::Operand, result: bool) -> Self::Entity;
+    fn push(items: &mut Self::Items, item: Self::Entity);
+    /// A solved [crate::Sequence], where `skipped` are the items that were short-circuited
+    fn sequence(logic: Logic, items: Self::Items, skipped: &[Entity], result: Tristate) -> Self::Entity;
+}
+
+/// Records the plain results
+pub(crate) struct Solve;
+
+impl Record for Solve {
+    type Operand = Value;
+    type Entity = bool;
+    type Items = ();
+
+    fn result(entity: &bool) -> Tristate {
+        Tristate::from(*entity)
+    }
+
+    fn comparison(_what: Value, _operator: Operator, _against: Value, result: bool) -> bool {
+        result
+    }
+
+    fn between(_what: Value, _from: Value, _to: Value, result: bool) -> bool {
+        result
+    }
+
+    fn not(entity: bool) -> bool {
+        !entity
+    }
+
+    fn predicate(_operand: Value, result: bool) -> bool {
+        result
+    }
+
+    fn push(_items: &mut (), _item: bool) {}
+
+    fn sequence(_logic: Logic, _items: (), _skipped: &[Entity], result: Tristate) -> bool {
+        // Without three-valued logic every solved item is known
+        result == Tristate::True
+    }
+}
+
+/// Records the results with three-valued (Kleene) logic, where an item on a variable that can not be resolved is
+/// [Tristate::Unknown]
+pub(crate) struct SolveTristate;
+
+impl Record for SolveTristate {
+    type Operand = Value;
+    type Entity = Tristate;
+    type Items = ();
+
+    fn result(entity: &Tristate) -> Tristate {
+        *entity
+    }
+
+    fn unsolved(error: Error) -> Result<Tristate> {
+        match error {
+            Error::UnresolvedVariable(_) => Ok(Tristate::Unknown),
+            error => Err(error),
+        }
+    }
+
+    fn comparison(_what: Value, _operator: Operator, _against: Value, result: bool) -> Tristate {
+        result.into()
+    }
+
+    fn between(_what: Value, _from: Value, _to: Value, result: bool) -> Tristate {
+        result.into()
+    }
+
+    fn not(entity: Tristate) -> Tristate {
+        !entity
+    }
+
+    fn predicate(_operand: Value, result: bool) -> Tristate {
+        result.into()
+    }
+
+    fn push(_items: &mut (), _item: Tristate) {}
+
+    fn sequence(_logic: Logic, _items: (), _skipped: &[Entity], result: Tristate) -> Tristate {
+        result
+    }
+}
+
+impl RecordOperand for Value {
+    fn value(&self) -> &Value {
+        self
+    }
+
+    fn fixed(value: Value) -> Self {
+        value
+    }
+
+    fn variable(_name: &str, value: Value) -> Self {
+        value
+    }
+
+    fn calculation(_lhs: Self, _arithmetic: Arithmetic, _rhs: Self, value: Value) -> Self {
+        value
+    }
+
+    fn negative(_operand: Self, value: Value) -> Self {
+        value
+    }
+
+    fn coalesce(_expression: &ComparisonType, used: Self, _missing: Option<&ComparisonType>) -> Self {
+        used
+    }
+
+    fn exists(_name: &str, value: Value) -> Self {
+        value
+    }
+
+    fn call(_name: &str, _arguments: Vec<Self>, value: Value) -> Self {
+        value
+    }
+}
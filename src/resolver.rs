@@ -1,8 +1,14 @@
-use crate::value::Value;
+use crate::{error::Result, value::Value};
 
 /// Resolves `name` to [Value] or [None] if there is no value for that given variable-`name`.
 /// Used for solving comparisons in [crate::solver::solve_tree] and [crate::evaluate].
 pub trait Resolver {
     /// Resolves `name` to a [Value] or [None] if there is no value for `name`
     fn resolve(&self, name: impl AsRef<str>) -> Option<&Value>;
+
+    /// Calls the custom function `name` on `arguments` or is [None] if there is no such function, so the
+    /// [crate::function::Builtin] of that name is called instead. See [crate::function::FunctionRegistry].
+    fn call(&self, _name: &str, _arguments: &[Value]) -> Option<Result<Value>> {
+        None
+    }
 }
@@ -1,16 +1,60 @@
-use crate::compare::{Comparison, Logic};
+use std::fmt::Display;
+
+use crate::compare::{Between, Comparison, ComparisonType, Logic};
 
 /// An entity in a [Sequence] which can be a another [Sequence] [Entity::Child] or a ComparisonType (Value or Variable) [Entity::Comparison],
 #[derive(Debug, PartialEq)]
 pub enum Entity {
     /// Another sequence which is encapsulated by the current sequence
-    Child(Sequence, Option<Logic>),
+    Child(Sequence),
     /// A comparison on the current layer
-    Comparison(Comparison, Option<Logic>),
+    Comparison(Comparison),
+    /// A range check on the current layer (`a between 1 and 5`)
+    Between(Between),
+    /// The negation of another [Entity] (`!` or `not`)
+    Not(Box<Entity>),
+    /// A single operand which must solve to [crate::Value::Bool] (e.G. `door.open` or `true`)
+    Predicate(ComparisonType),
 }
 
 /// A sequence of [Entity]s which themselfes represent another [Sequence] or a [Comparison].
+/// All `items` are combined by the same [Logic]. Precedence is expressed by nesting, so `a || b && c` is a
+/// [Logic::Or]-[Sequence] of `a` and a [Logic::And]-[Sequence] of `b` and `c`.
 #[derive(Debug, Default, PartialEq)]
 pub struct Sequence {
+    /// The [Logic] combining all `items`
+    pub logic: Logic,
     pub items: Vec<Entity>,
 }
+
+impl Display for Entity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Child(sequence) => write!(f, "({})", sequence),
+            Self::Comparison(comparison) => write!(f, "{}", comparison),
+            Self::Between(between) => write!(f, "{}", between),
+            Self::Not(entity) => write!(f, "!{}", entity),
+            Self::Predicate(operand) => write!(f, "{}", operand),
+        }
+    }
+}
+
+/// Writes the [Sequence] as expression which parses to the same [Sequence] again
+/// ```
+/// use metrics_evaluation::*;
+///
+/// let sequence = parse_tree("a > 1 || (b + 1) * 2 == c && !(d || not e)").unwrap();
+/// assert_eq!(sequence.to_string(), "a > 1 || ((b + 1) * 2 == c && !(d || !e))");
+/// assert_eq!(parse_tree(sequence.to_string()).unwrap(), sequence);
+/// ```
+impl Display for Sequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, item) in self.items.iter().enumerate() {
+            if index > 0 {
+                write!(f, " {} ", self.logic)?;
+            }
+            write!(f, "{}", item)?;
+        }
+        Ok(())
+    }
+}
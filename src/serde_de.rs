@@ -19,14 +19,14 @@ impl<'de> Visitor<'de> for SequenceVisitor {
     where
         E: de::Error,
     {
-        parse_tree(value).map_err(|e| E::custom(format!("Unable to parse '{}': {:?}", value, e)))
+        parse_tree(value).map_err(|e| E::custom(format!("Unable to parse '{}': {}", value, e)))
     }
 
     fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        parse_tree(&value).map_err(|e| E::custom(format!("Unable to parse '{}': {:?}", value, e)))
+        parse_tree(&value).map_err(|e| E::custom(format!("Unable to parse '{}': {}", value, e)))
     }
 }
 
@@ -50,7 +50,7 @@ mod tests {
     };
 
     #[test]
-    fn test_deserialize() -> anyhow::Result<()> {
+    fn test_deserialize() -> crate::Result<()> {
         use serde::de::{
             value::{Error as ValueError, StrDeserializer},
             IntoDeserializer,
@@ -60,19 +60,32 @@ mod tests {
 
         let deserializer: StrDeserializer<ValueError> = input.into_deserializer();
         let seq = Sequence::deserialize(deserializer).expect("Unable to deserialize");
+        assert_eq!(seq.logic, Logic::And);
         assert_eq!(
             seq.items[0],
-            Entity::Comparison(Comparison::from(("a", Operator::Greater, Value::Numeric(1.0))), None),
+            Entity::Comparison(Comparison::from(("a", Operator::Greater, Value::Numeric(1.0)))),
         );
 
         assert_eq!(
             seq.items[1],
-            Entity::Comparison(
-                Comparison::from(("b", Operator::Less, Value::Numeric(2.0))),
-                Some(Logic::And)
-            ),
+            Entity::Comparison(Comparison::from(("b", Operator::Less, Value::Numeric(2.0)))),
         );
 
         Ok(())
     }
+
+    #[test]
+    fn test_deserialize_error() {
+        use serde::de::{
+            value::{Error as ValueError, StrDeserializer},
+            IntoDeserializer,
+        };
+
+        let deserializer: StrDeserializer<ValueError> = "a > 1 b < 2".into_deserializer();
+        let error = Sequence::deserialize(deserializer).expect_err("Deserialized an invalid sequence");
+        assert_eq!(
+            error.to_string(),
+            "Unable to parse 'a > 1 b < 2': Syntax error at line 1, column 7: expected && or ||"
+        );
+    }
 }
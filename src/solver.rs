@@ -1,75 +1,171 @@
-use anyhow::{anyhow, Result};
-
 use crate::{
-    compare::{Compareable, Comparison, ComparisonType, Logic},
+    compare::{Between, Comparison, ComparisonType, Logic},
+    error::{Error, Result},
+    explain::{Explain, Explanation},
+    function::{FunctionRegistry, WithFunctions},
+    record::{Record, RecordOperand, Solve, SolveTristate},
     resolver::Resolver,
     sequence::{Entity, Sequence},
+    tristate::Tristate,
     value::Value,
-    Calculateable, Calculation,
+    Calculateable,
 };
 
-fn produce_final_value(input_value: Value, calculations: &Vec<Calculation>, resolver: &impl Resolver) -> Result<Value> {
-    let mut current = input_value;
-    for item in calculations {
-        let (item_value, item_arithmetic) = match item {
-            Calculation::Value(value, artihmetic) => (Some(value), artihmetic),
-            Calculation::Variable(name, artihmetic) => (resolver.resolve(name), artihmetic),
-        };
+/// Solves `operand`, which is recorded as `O`. All solvers and [explain_tree] solve operands through this.
+fn record_var<O: RecordOperand>(operand: &ComparisonType, resolver: &impl Resolver) -> Result<O> {
+    match operand {
+        ComparisonType::Value(value) => Ok(O::fixed(value.clone())),
+        ComparisonType::Variable(variable_name) => resolver
+            .resolve(variable_name)
+            .map(|value| O::variable(variable_name, value.clone()))
+            .ok_or_else(|| Error::UnresolvedVariable(variable_name.clone())),
+        ComparisonType::Calculation(calculation) => {
+            let lhs = record_var::<O>(&calculation.lhs, resolver)?;
+            let rhs = record_var::<O>(&calculation.rhs, resolver)?;
+            let value = lhs.value().clone().calculate(rhs.value(), calculation.arithmetic)?;
+            Ok(O::calculation(lhs, calculation.arithmetic, rhs, value))
+        }
+        ComparisonType::Negative(negated) => {
+            let negated = record_var::<O>(negated, resolver)?;
+            let value = (-negated.value().clone())?;
+            Ok(O::negative(negated, value))
+        }
+        ComparisonType::Coalesce(lhs, fallback) => match record_var::<O>(lhs, resolver) {
+            Err(Error::UnresolvedVariable(_)) => {
+                Ok(O::coalesce(operand, record_var::<O>(fallback, resolver)?, Some(lhs)))
+            }
+            Ok(used) if used.value() == &Value::Null => {
+                Ok(O::coalesce(operand, record_var::<O>(fallback, resolver)?, Some(lhs)))
+            }
+            used => Ok(O::coalesce(operand, used?, None)),
+        },
+        ComparisonType::Exists(name) => Ok(O::exists(name, Value::Bool(resolver.resolve(name).is_some()))),
+        ComparisonType::Pattern(pattern) => Ok(O::fixed(pattern.value())),
+        ComparisonType::Call(call) => {
+            let mut arguments = Vec::with_capacity(call.arguments.len());
+            for argument in &call.arguments {
+                arguments.push(record_var::<O>(argument, resolver)?);
+            }
+            let values: Vec<Value> = arguments.iter().map(|argument| argument.value().clone()).collect();
+            let value = match resolver.call(&call.name, &values) {
+                Some(result) => result?,
+                None => call.builtin()?.call(values)?,
+            };
+            Ok(O::call(&call.name, arguments, value))
+        }
+    }
+}
 
-        let value = item_value.ok_or_else(|| anyhow!("Unable to resolve variable {:?}", item))?;
+fn record_one<R: Record>(comparison: &Comparison, resolver: &impl Resolver) -> Result<R::Entity> {
+    let what = record_var::<R::Operand>(&comparison.what, resolver)?;
+    let against = record_var::<R::Operand>(&comparison.against, resolver)?;
+    let result = comparison.compare_values(what.value(), against.value())?;
 
-        current = current.calculate(value, *item_arithmetic)?;
-    }
+    Ok(R::comparison(what, comparison.operator, against, result))
+}
+
+fn record_between<R: Record>(between: &Between, resolver: &impl Resolver) -> Result<R::Entity> {
+    let what = record_var::<R::Operand>(&between.what, resolver)?;
+    let from = record_var::<R::Operand>(&between.from, resolver)?;
+    let to = record_var::<R::Operand>(&between.to, resolver)?;
+    let result = what.value().between(from.value(), to.value())?;
 
-    Ok(current)
+    Ok(R::between(what, from, to, result))
 }
 
-fn resolve_var(comparison: &ComparisonType, resolver: &impl Resolver) -> Result<Value> {
-    let (item_value, item_calculations) = match comparison {
-        ComparisonType::Value(ref value, ref value_calculations) => (Some(value), value_calculations),
-        ComparisonType::Variable(ref variable_name, ref variable_calculations) => {
-            (resolver.resolve(variable_name), variable_calculations)
+fn record_predicate<R: Record>(operand: &ComparisonType, resolver: &impl Resolver) -> Result<R::Entity> {
+    let operand = record_var::<R::Operand>(operand, resolver)?;
+    let result = match operand.value() {
+        Value::Bool(value) => *value,
+        value => {
+            return Err(Error::UnexpectedType {
+                expected: "Bool",
+                found: value.type_name(),
+            })
         }
     };
 
-    let value = item_value.ok_or_else(|| anyhow!("unable to resolve lhs in {:?}", comparison))?;
-    Ok(produce_final_value(value.clone(), item_calculations, resolver)?)
+    Ok(R::predicate(operand, result))
+}
+
+fn record_entity<R: Record>(entity: &Entity, resolver: &impl Resolver) -> Result<R::Entity> {
+    match entity {
+        Entity::Comparison(cmp) => record_one::<R>(cmp, resolver).or_else(R::unsolved),
+        Entity::Between(between) => record_between::<R>(between, resolver).or_else(R::unsolved),
+        Entity::Child(seq) => record_tree::<R>(seq, resolver),
+        Entity::Not(entity) => Ok(R::not(record_entity::<R>(entity, resolver)?)),
+        Entity::Predicate(operand) => record_predicate::<R>(operand, resolver).or_else(R::unsolved),
+    }
+}
+
+fn record_tree<R: Record>(sequence: &Sequence, resolver: &impl Resolver) -> Result<R::Entity> {
+    let mut items = R::Items::default();
+    let mut result = Tristate::from(sequence.logic == Logic::And);
+    for (index, entry) in sequence.items.iter().enumerate() {
+        let item = record_entity::<R>(entry, resolver)?;
+        let solved = R::result(&item);
+        R::push(&mut items, item);
+        // Skip all remaining items as soon as the result can not change anymore. An unknown result is only the
+        // result if nothing decides the sequence.
+        match (sequence.logic, solved) {
+            (Logic::And, Tristate::False) | (Logic::Or, Tristate::True) => {
+                return Ok(R::sequence(sequence.logic, items, &sequence.items[index + 1..], solved))
+            }
+            (_, Tristate::Unknown) => result = Tristate::Unknown,
+            _ => {}
+        }
+    }
+
+    Ok(R::sequence(sequence.logic, items, &[], result))
 }
 
 pub fn solve_one(comparison: &Comparison, resolver: &impl Resolver) -> Result<bool> {
-    let left_value = resolve_var(&comparison.what, resolver)?;
-    let right_value = resolve_var(&comparison.against, resolver)?;
+    record_one::<Solve>(comparison, resolver)
+}
 
-    let result = left_value.compare(&right_value, comparison.operator);
+pub fn solve_between(between: &Between, resolver: &impl Resolver) -> Result<bool> {
+    record_between::<Solve>(between, resolver)
+}
 
-    Ok(result)
+pub fn solve_predicate(operand: &ComparisonType, resolver: &impl Resolver) -> Result<bool> {
+    record_predicate::<Solve>(operand, resolver)
 }
 
 /// Solve a [Sequence] using the given 'resolver' to a final [bool].
 /// In practice, this function throws a sequence of comparisons against a given [Resolver] to evaluate a comparison to true or false.
+/// Like in rust, evaluation short-circuits: items which can not change the result anymore are not resolved at all.
 pub fn solve_tree(sequence: &Sequence, resolver: &impl Resolver) -> Result<bool> {
-    let mut result = true;
+    record_tree::<Solve>(sequence, resolver)
+}
 
-    for entry in &sequence.items {
-        let (child_result, logic) = match entry {
-            Entity::Comparison(cmp, logic) => (solve_one(&cmp, resolver)?, logic),
-            Entity::Child(seq, logic) => (solve_tree(&seq, resolver)?, logic),
-        };
+/// Solve a [Sequence] like [solve_tree] does, calling the custom `functions` besides the
+/// [crate::function::Builtin]s. Use [WithFunctions] to pass `functions` to the other solvers.
+pub fn solve_tree_with_functions(
+    sequence: &Sequence,
+    resolver: &impl Resolver,
+    functions: &FunctionRegistry,
+) -> Result<bool> {
+    solve_tree(sequence, &WithFunctions::new(resolver, functions))
+}
 
-        match logic {
-            Some(Logic::And) => result &= child_result,
-            Some(Logic::Or) => result |= child_result,
-            None => result = child_result,
-        }
-    }
+/// Solve a [Sequence] like [solve_tree] does but with three-valued (Kleene) logic: a comparison or predicate on a
+/// variable that can not be resolved is [Tristate::Unknown] instead of an [Error::UnresolvedVariable].
+pub fn solve_tree_tristate(sequence: &Sequence, resolver: &impl Resolver) -> Result<Tristate> {
+    record_tree::<SolveTristate>(sequence, resolver)
+}
 
-    Ok(result)
+/// Solve a [Sequence] like [solve_tree] does but trace every step into an [Explanation] which tells why the
+/// [Sequence] solved to its result.
+pub fn explain_tree(sequence: &Sequence, resolver: &impl Resolver) -> Result<Explanation> {
+    record_tree::<Explain>(sequence, resolver)
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
+    use anyhow::Result;
+
     use super::*;
     use crate::MapResolver;
 
@@ -84,15 +180,15 @@ mod tests {
         values.insert("bar_baz", 4);
         let values = MapResolver::from(values);
 
-        assert_eq!(evaluate(r#"a == b - 1"#, &values)?, true);
-        assert_eq!(evaluate(r#"b == a + a"#, &values)?, true);
-        assert_eq!(evaluate(r#"a < b"#, &values)?, true);
-        assert_eq!(evaluate(r#"a + 2 == b + 1"#, &values)?, true);
-        assert_eq!(evaluate(r#"a >= b"#, &values)?, false);
-        assert_eq!(evaluate(r#"b == a"#, &values)?, false);
-        assert_eq!(evaluate(r#"b > a"#, &values)?, true);
-        assert_eq!(evaluate(r#"foo.bar < bar_baz"#, &values)?, true);
-        assert_eq!(evaluate(r#"bar_baz > b"#, &values)?, true);
+        assert!(evaluate(r#"a == b - 1"#, &values)?);
+        assert!(evaluate(r#"b == a + a"#, &values)?);
+        assert!(evaluate(r#"a < b"#, &values)?);
+        assert!(evaluate(r#"a + 2 == b + 1"#, &values)?);
+        assert!(!evaluate(r#"a >= b"#, &values)?);
+        assert!(!evaluate(r#"b == a"#, &values)?);
+        assert!(evaluate(r#"b > a"#, &values)?);
+        assert!(evaluate(r#"foo.bar < bar_baz"#, &values)?);
+        assert!(evaluate(r#"bar_baz > b"#, &values)?);
 
         Ok(())
     }
@@ -105,9 +201,9 @@ mod tests {
         values.insert("a", chrono::Duration::days(1).to_std()?);
         let values = MapResolver::from(values);
 
-        assert_eq!(evaluate(r#"a < "1 d 1h""#, &values)?, true);
-        assert_eq!(evaluate(r#"a > "2h 5min""#, &values)?, true);
-        assert_eq!(evaluate(r#"a <= "5h 12min 42sec""#, &values)?, false);
+        assert!(evaluate(r#"a < "1 d 1h""#, &values)?);
+        assert!(evaluate(r#"a > "2h 5min""#, &values)?);
+        assert!(!evaluate(r#"a <= "5h 12min 42sec""#, &values)?);
 
         Ok(())
     }
@@ -176,7 +272,7 @@ mod tests {
             open: true,
             open_since_minutes: 120,
             room_tempereature: 1.0,
-            current_time: NaiveTime::from_hms(01, 1, 0),
+            current_time: NaiveTime::from_hms(1, 1, 0),
             expected_to_trigger: false,
         },
         Test {
@@ -213,7 +309,7 @@ mod tests {
             values.insert("window.contact.value", Value::Bool(!test.open));
             values.insert(
                 "window.contact.since",
-                Value::Duration(core::time::Duration::from_secs(test.open_since_minutes * 60)),
+                Value::from(core::time::Duration::from_secs(test.open_since_minutes * 60)),
             );
             values.insert("room.temperature.value", Value::Numeric(test.room_tempereature));
             values.insert("compute.time.now", Value::Time(test.current_time));
@@ -238,12 +334,12 @@ mod tests {
 
         let values = MapResolver::from(values);
 
-        assert_eq!(evaluate(r#"start <= "15:00:00""#, &values)?, true);
-        assert_eq!(evaluate(r#"end >= "15:00:00""#, &values)?, true);
-        assert_eq!(evaluate(r#"now >= "22:00:00""#, &values)?, false);
-        assert_eq!(evaluate(r#"now <= "05:00:00""#, &values)?, false);
-        assert_eq!(evaluate(r#"now >= "22:00:00" || now <= "05:00:00""#, &values)?, false);
-        assert_eq!(evaluate(r#"now >= start && now <= end"#, &values)?, true);
+        assert!(evaluate(r#"start <= "15:00:00""#, &values)?);
+        assert!(evaluate(r#"end >= "15:00:00""#, &values)?);
+        assert!(!evaluate(r#"now >= "22:00:00""#, &values)?);
+        assert!(!evaluate(r#"now <= "05:00:00""#, &values)?);
+        assert!(!evaluate(r#"now >= "22:00:00" || now <= "05:00:00""#, &values)?);
+        assert!(evaluate(r#"now >= start && now <= end"#, &values)?);
 
         Ok(())
     }
@@ -259,26 +355,1256 @@ mod tests {
         values.insert("d", 4);
         let values = MapResolver::from(values);
 
-        assert_eq!(evaluate("a < 99", &values)?, true);
-        assert_eq!(evaluate("a > 2", &values)?, false);
-        assert_eq!(evaluate("b <= 2", &values)?, true);
-        assert_eq!(evaluate("c >= 3", &values)?, true);
-        assert_eq!(evaluate("a != 99 && (b == 2 || c == 99)", &values)?, true);
+        assert!(evaluate("a < 99", &values)?);
+        assert!(!evaluate("a > 2", &values)?);
+        assert!(evaluate("b <= 2", &values)?);
+        assert!(evaluate("c >= 3", &values)?);
+        assert!(evaluate("a != 99 && (b == 2 || c == 99)", &values)?);
+        assert!(evaluate(
+            "a == 42 || ((b == 2 || b == 3) && (c == 3 || c == 4))",
+            &values
+        )?);
+        assert!(!evaluate(
+            "a == 4711 || ((b == 42 || b == 2) && (c == 3 && c == 4))",
+            &values
+        )?);
+
+        assert!(evaluate("((a == 1) && (b == 2))", &values)?);
+        assert!(evaluate("((((a == 1))))", &values)?);
+        assert!(evaluate("((((a == 1)))) && b == 2", &values)?);
+        assert!(!evaluate("(a == 1) && b == 3", &values)?);
+        assert!(evaluate("b == 2 && (a == 1)", &values)?);
+        assert!(evaluate("(b == 2) && (a == 1)", &values)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_left_hand_values() -> Result<()> {
+        use chrono::naive::NaiveTime;
+
+        use crate::evaluate;
+
+        let mut values = HashMap::new();
+        values.insert("room1.temp", Value::Numeric(20.0));
+        values.insert("true_flag", Value::Bool(true));
+        values.insert("now_time", Value::Time(NaiveTime::from_hms(9, 0, 0)));
+        let values = MapResolver::from(values);
+
+        assert!(evaluate("100 > room1.temp", &values)?);
+        assert!(!evaluate("10 >= room1.temp", &values)?);
+        assert!(evaluate(r#""08:00:00" < now_time"#, &values)?);
+        assert!(evaluate("18 + 2 == room1.temp", &values)?);
+        assert!(evaluate("10 * 2 == room1.temp - 1 + 1", &values)?);
+        assert!(evaluate("true == true_flag && 1 < 2", &values)?);
+        assert!(evaluate("room1.temp > 10 && 42 == 42", &values)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_precedence() -> Result<()> {
+        use crate::evaluate;
+
+        let mut values = HashMap::new();
+        values.insert("a", 1);
+        values.insert("b", 2);
+        values.insert("c", 3);
+        let values = MapResolver::from(values);
+
+        // `&&` binds tighter than `||`
+        assert!(evaluate("a == 1 || b == 2 && c == 4", &values)?);
+        assert!(evaluate("b == 2 && c == 4 || a == 1", &values)?);
+        assert!(!evaluate("(a == 1 || b == 2) && c == 4", &values)?);
+        assert!(evaluate("a == 9 || b == 2 && c == 3 || c == 9", &values)?);
+        assert!(!evaluate("a == 9 || b == 2 && c == 9 || c == 9", &values)?);
+        assert!(evaluate("a == 1 and b == 9 or c == 3", &values)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_arithmetic_precedence() -> Result<()> {
+        use crate::evaluate;
+
+        let mut values = HashMap::new();
+        values.insert("a", 1);
+        values.insert("b", 5);
+        let values = MapResolver::from(values);
+
+        assert!(evaluate("a + 2 * 3 == 7", &values)?);
+        assert!(evaluate("2 * 3 + a == 7", &values)?);
+        assert!(evaluate("(a + 2) * 3 == 9", &values)?);
+        assert!(evaluate("(a + b) / 2 > 2", &values)?);
+        assert!(evaluate("(a + b) * (b - a) == 24", &values)?);
+        assert!(evaluate("((a + b)) / (2) == 3", &values)?);
+        assert!(evaluate("10 - 2 - 3 == b", &values)?);
+        assert!(evaluate("20 / 2 / 2 == b", &values)?);
+        assert!(evaluate("b - a * 2 + 1 == 4", &values)?);
+        assert!(evaluate("((a + b) / 2 == 3) && b == 5", &values)?);
+        assert!(evaluate("a == 2 || (a + b) / 2 == 3", &values)?);
+        assert!(evaluate("(a == 1 || b == 2) && (a + 1) * 2 == 4", &values)?);
+        assert!(evaluate("a + 1 == 2 && b * 2 == 10", &values)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_remainder_power() -> Result<()> {
+        use chrono::NaiveTime;
+
+        use crate::{evaluate, parse_tree, Error};
+
+        let mut values = HashMap::new();
+        values.insert("minute", Value::Integer(45));
+        values.insert("error", Value::Numeric(-1.5));
+        values.insert("now", Value::from(NaiveTime::from_hms(8, 30, 0)));
+        values.insert("uptime", Value::from(std::time::Duration::from_secs(100 * 60)));
+        let values = MapResolver::from(values);
+
+        assert!(evaluate("minute % 15 == 0 && minute % 7 == 3", &values)?);
+        assert!(evaluate("-7 % 3 == 2 && 7.5 % 2 == 1.5", &values)?);
+        assert!(evaluate(
+            "minute // 10 == 4 && -7 // 2 == -4 && 7.5 // 2 == 3",
+            &values
+        )?);
+        assert!(evaluate("error ** 2 == 2.25 && error ^ 2 == 2.25", &values)?);
+        assert!(evaluate(
+            "2 ** 10 == 1024 && 2 ** -2 == 0.25 && 9 ** 0.5 == 3",
+            &values
+        )?);
+        // Exponentiation binds tighter than negations and products and is right-associative
+        assert!(evaluate("-2 ** 2 == -4 && (-2) ** 2 == 4", &values)?);
+        assert!(evaluate("2 * 3 ** 2 == 18 && 2 ** 3 ** 2 == 512", &values)?);
+        assert!(evaluate("minute % 4 * 2 == 2 && 1 + 7 // 2 == 4", &values)?);
+        // Exponents beyond u32 are exact for bases which can not overflow
+        assert!(evaluate("1 ** 5000000000 == 1 && 0 ** 5000000000 == 0", &values)?);
+        assert!(evaluate(
+            "(-1) ** 5000000000 == 1 && (-1) ** 5000000001 == -1",
+            &values
+        )?);
+
+        // Durations and times of day
+        assert!(evaluate(
+            r#"uptime % "1h" == "40min" && uptime // "30min" == 3"#,
+            &values
+        )?);
+        assert!(evaluate(r#"now % "15min" == "0s" && now // "1h" == 8"#, &values)?);
+        assert!(evaluate(r#"-"90min" % "1h" == "30min""#, &values)?);
+
+        assert_eq!(evaluate("minute % 0 == 0", &values), Err(Error::DivisionByZero));
+        assert_eq!(evaluate("0 ** -1 > 0", &values), Err(Error::DivisionByZero));
+        assert_eq!(evaluate("0 ^ -2 > 0", &values), Err(Error::DivisionByZero));
+        assert_eq!(evaluate("0.0 ** -0.5 > 0", &values), Err(Error::DivisionByZero));
+        assert_eq!(evaluate(r#"uptime // "0s" == 0"#, &values), Err(Error::DivisionByZero));
+        assert!(matches!(
+            evaluate("error ** 0.5 > 0", &values),
+            Err(Error::OutOfRange(_))
+        ));
+        assert!(matches!(
+            evaluate("minute ** 100 > 0", &values),
+            Err(Error::OutOfRange(_))
+        ));
+        assert!(matches!(
+            evaluate(r#"uptime ** 2 > 0"#, &values),
+            Err(Error::IncompatibleTypes { .. })
+        ));
+
+        let sequence = parse_tree("(-a) ** 2 + -2 ** b ^ c // 2 % d > 0")?;
+        assert_eq!(sequence.to_string(), "((-a) ** 2) + ((-(2 ** (b ** c)) // 2) % d) > 0");
+        assert_eq!(parse_tree(sequence.to_string())?, sequence);
+        assert_eq!(parse_tree("(-2) ** 2 > 0")?.to_string(), "(-2) ** 2 > 0");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_number_literals() -> Result<()> {
+        use crate::evaluate;
+
+        let mut values = HashMap::new();
+        values.insert("outdoor.temp", Value::Numeric(-3.5));
+        values.insert("ratio", Value::Numeric(0.0005));
+        values.insert("a", Value::Numeric(-5.0));
+        values.insert("e1", Value::Numeric(1.0));
+        let values = MapResolver::from(values);
+
+        assert!(evaluate("outdoor.temp < 0", &values)?);
+        assert!(evaluate("outdoor.temp == -3.5", &values)?);
+        assert!(evaluate("outdoor.temp > -4 && outdoor.temp < -3.25", &values)?);
+        assert!(evaluate("-3.5 == outdoor.temp", &values)?);
+        assert!(evaluate("ratio < 1e-3 && ratio > 5E-5", &values)?);
+        assert!(evaluate("ratio * 1e4 == 5", &values)?);
+        assert!(evaluate(".5 + .5 == 1.", &values)?);
+        assert!(evaluate("-a > 3", &values)?);
+        assert!(evaluate("-a * 2 == 10", &values)?);
+        assert!(evaluate("2 - -a == -3", &values)?);
+        assert!(evaluate("-(a + 1) == 4", &values)?);
+        assert!(evaluate("e1 == 1", &values)?);
+        assert!(evaluate("1 - 1 == 0", &values)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_negation() -> Result<()> {
+        use crate::evaluate;
+
+        let mut values = HashMap::new();
+        values.insert("a", 1);
+        values.insert("b", 2);
+        values.insert("notify", 3);
+        values.insert("orders", 4);
+        let values = MapResolver::from(values);
+
+        assert!(evaluate("!(a > 1 && b == 2)", &values)?);
+        assert!(!evaluate("!(a == 1 && b == 2)", &values)?);
+        assert!(evaluate("not a > 1", &values)?);
+        assert!(evaluate("not(a > 1)", &values)?);
+        assert!(evaluate("!!(a == 1)", &values)?);
+        assert!(evaluate("not !a == 1", &values)?);
+        assert!(evaluate("a == 1 && !b == 1", &values)?);
+        assert!(evaluate("a == 2 || not (b == 1 || a == 2)", &values)?);
+        // `!` binds tighter than `&&` and `||`
+        assert!(!evaluate("!a == 1 && b == 2", &values)?);
+        assert!(evaluate("!a == 1 || b == 2", &values)?);
+        // Keywords do not swallow identifiers beginning alike
+        assert!(evaluate("notify == 3 and orders == 4", &values)?);
+        assert!(evaluate("a != 2", &values)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_predicate() -> Result<()> {
+        use crate::evaluate;
+
+        let mut values = HashMap::new();
+        values.insert("door.open", Value::Bool(true));
+        values.insert("window.open", Value::Bool(false));
+        values.insert("temp", Value::Numeric(21.0));
+        let values = MapResolver::from(values);
+
+        assert!(evaluate("door.open", &values)?);
+        assert!(!evaluate("window.open", &values)?);
+        assert!(evaluate("door.open && temp > 20", &values)?);
+        assert!(evaluate("temp > 20 && door.open", &values)?);
+        assert!(evaluate("window.open || door.open", &values)?);
+        assert!(evaluate("!window.open && (door.open)", &values)?);
+        assert!(evaluate("not window.open", &values)?);
+        assert!(evaluate("true", &values)?);
+        assert!(!evaluate("false || window.open", &values)?);
+        assert!(evaluate("door.open == true && true", &values)?);
+
+        // Anything but a bool is not a predicate
+        assert!(evaluate("temp", &values).is_err());
+        assert!(evaluate("door.open && temp + 1", &values).is_err());
+        assert!(evaluate("door.open temp", &values).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_tristate() -> Result<()> {
+        use crate::evaluate_tristate;
+
+        let mut values = HashMap::new();
+        values.insert("a", Value::Numeric(1.0));
+        values.insert("open", Value::Bool(true));
+        let values = MapResolver::from(values);
+
+        assert_eq!(evaluate_tristate("a == 1 && open", &values)?, Tristate::True);
+        assert_eq!(evaluate_tristate("a == 2 || !open", &values)?, Tristate::False);
+        assert_eq!(evaluate_tristate("missing > 1 || a == 1", &values)?, Tristate::True);
+        assert_eq!(evaluate_tristate("missing > 1 && a == 2", &values)?, Tristate::False);
+        assert_eq!(evaluate_tristate("missing > 1 || a == 2", &values)?, Tristate::Unknown);
+        assert_eq!(
+            evaluate_tristate("a == 1 && (missing || b < 1)", &values)?,
+            Tristate::Unknown
+        );
+        assert_eq!(evaluate_tristate("not missing", &values)?, Tristate::Unknown);
+        assert_eq!(evaluate_tristate("a + missing > 1 || open", &values)?, Tristate::True);
+        assert_eq!(Tristate::Unknown.known(), None);
+        // Anything but an unresolvable variable still fails
+        assert!(evaluate_tristate("a", &values).is_err());
+        assert!(evaluate_tristate("a >", &values).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_coalesce() -> Result<()> {
+        use crate::{evaluate, explain, parse_tree};
+
+        let mut values = HashMap::new();
+        values.insert("battery.level", Value::Numeric(10.0));
+        values.insert("exists", Value::Numeric(1.0));
+        let values = MapResolver::from(values);
+
+        assert!(evaluate("battery.level ?? 100 < 20", &values)?);
+        assert!(!evaluate("missing ?? 100 < 20", &values)?);
+        assert!(evaluate("missing ?? other ?? 5 == 5", &values)?);
+        assert!(evaluate("(missing ?? 2) * 3 == 6", &values)?);
+        assert!(evaluate("missing ?? 2 + 3 == 5", &values)?);
+        assert!(evaluate("missing + 1 ?? 0 == 0", &values)?);
+        assert!(evaluate("exists(battery.level) && !exists(missing)", &values)?);
+        assert!(evaluate("exists( battery.level ) == true", &values)?);
+        assert!(evaluate("exists == 1", &values)?);
+        assert!(!evaluate("exists(missing) && missing > 1", &values)?);
+        // Only unresolvable variables fall back
+        assert!(evaluate(r#"battery.level + "foo" ?? 1 == 1"#, &values).is_err());
+        assert!(evaluate("missing ?? == 1", &values).is_err());
+        assert!(evaluate("exists(1) == 1", &values).is_err());
+
+        let sequence = parse_tree("(a ?? 1) * 2 > b ?? c ?? 3 && exists(d)")?;
+        assert_eq!(sequence.to_string(), "(a ?? 1) * 2 > b ?? c ?? 3 && exists(d)");
+        assert_eq!(parse_tree(sequence.to_string())?, sequence);
+
+        assert_eq!(
+            explain("missing ?? battery.level < 20", &values)?.to_string(),
+            "\
+&& => true
+  missing ?? battery.level < 20 => true
+    missing has no value
+    battery.level = 10
+    missing ?? battery.level = 10
+    10 < 20
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_integer() -> Result<()> {
+        use crate::{evaluate, Error};
+
+        let mut values = HashMap::new();
+        values.insert("bytes", Value::from(9_007_199_254_740_993i64));
+        values.insert("packets", Value::from(u64::MAX));
+        values.insert("ratio", Value::Numeric(0.5));
+        let values = MapResolver::from(values);
+
+        // 2^53 + 1 can not be represented as f64
+        assert!(evaluate("bytes == 9007199254740993", &values)?);
+        assert!(evaluate("bytes != 9007199254740992", &values)?);
+        assert!(evaluate("bytes > 9007199254740992.0", &values)?);
+        assert!(evaluate("bytes - 1 == 9007199254740992", &values)?);
+        assert!(evaluate("packets == 18446744073709551615 && packets > bytes", &values)?);
+        assert!(evaluate("packets - bytes > 0 && -bytes < 0", &values)?);
+        assert!(evaluate(
+            "1 == 1.0 && 2 > 1.5 && ratio < 1 && 3 * ratio == 1.5",
+            &values
+        )?);
+        assert!(evaluate("7 / 2 == 3.5 && 6 / 2 == 3", &values)?);
+        assert!(evaluate("-9223372036854775808 < 0", &values)?);
+        assert_eq!(Value::from(42u64), Value::Integer(42));
+        assert!(matches!(Value::from(u64::MAX), Value::Unsigned(u64::MAX)));
+        assert!(matches!(
+            evaluate("packets + 1 > 0", &values),
+            Err(Error::OutOfRange(_))
+        ));
+        assert!(matches!(
+            evaluate("packets * packets > 0", &values),
+            Err(Error::OutOfRange(_))
+        ));
+        assert!(matches!(evaluate("-packets < 0", &values), Err(Error::OutOfRange(_))));
+        assert_eq!(evaluate("bytes / 0 > 0", &values), Err(Error::DivisionByZero));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_dates() -> Result<()> {
+        use chrono::{DateTime, NaiveDate};
+
+        use crate::{evaluate, Error};
+
+        let mut values = HashMap::new();
+        values.insert("today", Value::from(NaiveDate::from_ymd(2026, 10, 17)));
+        values.insert(
+            "now",
+            Value::from(DateTime::parse_from_rfc3339("2026-10-17T12:00:00+02:00")?),
+        );
+        values.insert(
+            "last_seen",
+            Value::from(DateTime::parse_from_rfc3339("2026-10-14T23:30:00Z")?),
+        );
+        let values = MapResolver::from(values);
+
+        assert!(evaluate(r#"today < "2026-12-24" && today == "2026-10-17""#, &values)?);
+        assert!(evaluate(
+            r#"today + "2days" == "2026-10-19" && today - "1day" == "2026-10-16""#,
+            &values
+        )?);
+        assert!(evaluate(r#""2026-12-24" - today == "68days""#, &values)?);
+        // Date-times compare by the instant regardless of their offset
+        assert!(evaluate(
+            r#"now == "2026-10-17T10:00:00Z" && now > "2026-10-17T11:00:00+02:00""#,
+            &values
+        )?);
+        assert!(evaluate(r#"now == "2026-10-17T10:00:00""#, &values)?);
+        assert!(evaluate(
+            r#"now - last_seen > "2days" && last_seen + "2days" < now"#,
+            &values
+        )?);
+        assert!(evaluate(r#"now - "1h" == "2026-10-17T09:00:00Z""#, &values)?);
+        assert!(evaluate(r#"now - "2026-10-17T09:30:00+00:00" == "30min""#, &values)?);
+        assert!(matches!(
+            evaluate(r#"today == "2026-10-17T10:00:00Z""#, &values),
+            Err(Error::IncompatibleTypes { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_signed_duration() -> Result<()> {
+        use chrono::{DateTime, Duration, NaiveTime};
+
+        use crate::{evaluate, Error};
+
+        let mut values = HashMap::new();
+        values.insert("uptime", Value::Duration(Duration::minutes(30)));
+        values.insert("start", Value::from(NaiveTime::from_hms(22, 0, 0)));
+        values.insert("end", Value::from(NaiveTime::from_hms(6, 30, 0)));
+        values.insert(
+            "now",
+            Value::from(DateTime::parse_from_rfc3339("2026-10-17T12:00:00+02:00")?),
+        );
+        values.insert(
+            "last_seen",
+            Value::from(DateTime::parse_from_rfc3339("2026-10-17T12:00:00Z")?),
+        );
+        let values = MapResolver::from(values);
+
+        assert!(evaluate(
+            r#"uptime - "1h" < "0s" && uptime - "1h" == -"30min""#,
+            &values
+        )?);
+        assert!(evaluate(r#"uptime - "1h" + "2h" == "1h 30min""#, &values)?);
+        assert!(evaluate(r#"-uptime < uptime && -(-uptime) == uptime"#, &values)?);
+        assert!(evaluate(
+            r#"start - end == "15h 30min" && end - start == -"15h 30min""#,
+            &values
+        )?);
+        assert!(evaluate(r#""08:00:00" - end > "1h""#, &values)?);
+        // `last_seen` is two hours after `now`
+        assert!(evaluate(
+            r#"now - last_seen == -"2h" && last_seen - now == "2h""#,
+            &values
+        )?);
+        assert!(evaluate(
+            r#"uptime * 3 == "1h 30min" && uptime / 4 == "7min 30s""#,
+            &values
+        )?);
+        assert!(evaluate(r#"uptime * 1.5 == "45min" && uptime * -2 == -"1h""#, &values)?);
+        assert!(evaluate(r#"1.5 * uptime == "45min" && -2 * uptime == -"1h""#, &values)?);
+        assert!(matches!(
+            evaluate(r#"uptime / 0 > "0s""#, &values),
+            Err(Error::DivisionByZero)
+        ));
+        assert!(matches!(
+            evaluate(r#"uptime * 1e300 > "0s""#, &values),
+            Err(Error::OutOfRange(_))
+        ));
+        assert_eq!(Value::Duration(Duration::minutes(-90)).to_string(), "-1h 30m");
+        assert_eq!(
+            Value::from(std::time::Duration::from_secs(90)),
+            Value::Duration(Duration::seconds(90))
+        );
+        assert_eq!(
+            Value::from(std::time::Duration::MAX),
+            Value::Duration(Duration::max_value())
+        );
+        assert!(matches!(
+            Value::try_from_std(std::time::Duration::MAX),
+            Err(Error::OutOfRange(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_list() -> Result<()> {
+        use crate::{evaluate, parse_tree, Error};
+
+        let mut values = HashMap::new();
+        values.insert("mode", Value::from("away"));
+        values.insert("level", Value::Integer(2));
+        values.insert("alarms", Value::from(vec!["smoke", "water"]));
+        values.insert("no_alarms", Value::List(Vec::new()));
+        let values = MapResolver::from(values);
+
+        assert!(evaluate(r#"mode in ["eco", "away", "vacation"]"#, &values)?);
+        assert!(!evaluate(r#"mode not in ["eco", "away",]"#, &values)?);
+        assert!(evaluate("level in [1, 2.0, 3] && level not in [-2, []]", &values)?);
+        assert!(evaluate("level + 1 in [3] and -level in [-2]", &values)?);
+        // Lists may mix types, items of another type are not equal
+        assert!(evaluate(r#"level in ["2", null, 2]"#, &values)?);
+        assert!(evaluate(r#""water" in alarms && "fire" not in alarms"#, &values)?);
+        assert!(evaluate(r#""smoke" not in no_alarms && no_alarms == []"#, &values)?);
+        assert!(evaluate(
+            r#"alarms == ["smoke", "water"] && alarms != ["water", "smoke"]"#,
+            &values
+        )?);
+        assert!(evaluate(r#"["1h", -"5min"] == ["60min", -"300s"]"#, &values)?);
+        assert!(evaluate("[1, 2] in [[1, 2], [3]]", &values)?);
+        assert!(evaluate(
+            "notify in [true]",
+            &MapResolver::from(HashMap::from([("notify", true)]))
+        )?);
+        assert!(matches!(
+            evaluate(r#"mode in "away""#, &values),
+            Err(Error::IncompatibleTypes { .. })
+        ));
+        assert!(matches!(
+            evaluate("alarms > [1]", &values),
+            Err(Error::IncompatibleTypes { .. })
+        ));
+        assert!(matches!(
+            evaluate("level in [1, 2", &values),
+            Err(Error::Syntax { offset: 14, .. })
+        ));
+        assert!(matches!(
+            evaluate(r#"level in [-"eco"]"#, &values),
+            Err(Error::Syntax { .. })
+        ));
+
+        let sequence = parse_tree(r#"mode not in ["eco", -"1h", [1, -2.5]] || level in []"#)?;
+        assert_eq!(
+            sequence.to_string(),
+            r#"mode not in ["eco", -d"1h", [1, -2.5]] || level in []"#
+        );
+        assert_eq!(parse_tree(sequence.to_string())?, sequence);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_string_literals() -> Result<()> {
+        use crate::{evaluate, parse_tree, Error};
+
+        let mut values = HashMap::new();
+        values.insert("message", Value::from("it's \"done\""));
+        values.insert("path", Value::from(r"C:\data"));
+        values.insert("lines", Value::from("a\nb"));
+        values.insert("empty", Value::from(""));
+        values.insert("unit", Value::from("°C"));
+        let values = MapResolver::from(values);
+
+        // Quotes must match, the other quote can be used within
+        assert!(evaluate(
+            r#"message == 'it\'s "done"' && message starts_with "it's""#,
+            &values
+        )?);
+        assert!(evaluate(r#"message == "it's \"done\"""#, &values)?);
+        assert!(evaluate(r#"path == "C:\\data" && path == 'C:\data'"#, &values)?);
+        assert!(evaluate(r#"lines == "a\nb" && lines contains "\n""#, &values)?);
+        assert!(evaluate(r#"unit == "\u{b0}C" && unit == "\u{00B0}\u{43}""#, &values)?);
+        assert!(evaluate(r#"empty == "" && empty == '' && "" != "a""#, &values)?);
+        assert!(evaluate(r#"empty + "x" == "x" && len("") == 0"#, &values)?);
+
+        assert!(matches!(
+            parse_tree(r#"a == 'foo""#),
+            Err(Error::Syntax { offset: 10, .. })
+        ));
+        assert!(matches!(
+            parse_tree(r#"a == "foo"#),
+            Err(Error::Syntax { offset: 9, ref expected, .. }) if expected == &vec!["\""]
+        ));
+        assert!(matches!(
+            parse_tree(r#"a == "\u{110000}""#),
+            Err(Error::Syntax { offset: 6, ref expected, .. }) if expected == &vec!["escape sequence"]
+        ));
+
+        let sequence = parse_tree(r#"a == "say \"hi\"\n" || b == 'it\'s' || c =~ "^v1\.\d+" || d == "\u{7}\\""#)?;
+        assert_eq!(
+            sequence.to_string(),
+            r#"a == "say \"hi\"\n" || b == "it's" || c =~ "^v1\.\d+" || d == "\u{7}\\""#
+        );
+        assert_eq!(parse_tree(sequence.to_string())?, sequence);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_typed_literals() -> Result<()> {
+        use chrono::{NaiveDate, NaiveTime};
+
+        use crate::{
+            evaluate,
+            expr_parser::{parse_tree_with_options, ParseOptions},
+            function::FunctionRegistry,
+            parse_tree, Error,
+        };
+
+        let mut values = HashMap::new();
+        values.insert("device", Value::from("1m"));
+        values.insert("shift", Value::from("12:00:00"));
+        values.insert("timeout", Value::from("90s"));
+        values.insert("now", Value::from(NaiveTime::from_hms(8, 30, 0)));
+        values.insert("today", Value::from(NaiveDate::from_ymd(2026, 12, 24)));
+        let values = MapResolver::from(values);
+
+        // Plain string literals are guessed to be a duration, time, date or date-time by default
+        assert!(evaluate(r#"device == "1m""#, &values).is_err());
+        // Typed literals are never guessed
+        assert!(evaluate(r#"device == s"1m" && shift == s'12:00:00'"#, &values)?);
+        assert!(evaluate(
+            r#"d"1m" == "60s" && t"08:00:00" < now && now - t"08:00:00" == d"30min""#,
+            &values
+        )?);
+        assert!(evaluate(
+            r#"s"5min" + "x" == "5minx" && [d"1m", s"1m"] contains d"60s""#,
+            &values
+        )?);
+
+        // Conversions read strings of variables as well
+        assert!(evaluate(
+            r#"duration(timeout) == "1min 30s" && time(shift) > now"#,
+            &values
+        )?);
+        assert!(evaluate(
+            r#"date("2026-12-24") == today && datetime("2026-12-24T18:00:00+01:00") == "2026-12-24T17:00:00Z""#,
+            &values
+        )?);
+        assert!(evaluate(r#"duration("1h") == "1h" && time(now) == now"#, &values)?);
+        assert_eq!(
+            evaluate("time(device) > now", &values),
+            Err(Error::InvalidConversion {
+                to: "Time",
+                value: "1m".into()
+            })
+        );
+        assert!(matches!(
+            evaluate("date(now) > today", &values),
+            Err(Error::IncompatibleType { .. })
+        ));
+
+        // Invalid typed literals are rejected on parsing
+        assert!(matches!(
+            parse_tree(r#"a > d"soon""#),
+            Err(Error::Syntax { offset: 5, ref expected, .. }) if expected == &vec!["duration"]
+        ));
+        assert!(matches!(
+            parse_tree(r#"a > t"25:00:00""#),
+            Err(Error::Syntax { offset: 5, ref expected, .. }) if expected == &vec!["time"]
+        ));
+        assert!(matches!(parse_tree(r#"a > s"x"#), Err(Error::Syntax { offset: 7, .. })));
+        // A variable named like a prefix is still a variable
+        assert_eq!(
+            parse_tree("d > 1 && t == s && s != d")?.to_string(),
+            "d > 1 && t == s && s != d"
+        );
+
+        // Without guessing, plain string literals are always strings
+        let options = ParseOptions { guess_literals: false };
+        let functions = FunctionRegistry::default();
+        let sequence = parse_tree_with_options(
+            r#"device == "1m" && shift == "12:00:00" && time(shift) > now && duration("90s") == d"1m 30s""#,
+            options,
+            &functions,
+        )?;
+        assert!(solve_tree(&sequence, &values)?);
+        assert!(solve_tree(
+            &parse_tree_with_options(r#"device in ["1m", "2m"] && (now < t"12:00:00")"#, options, &functions)?,
+            &values
+        )?);
+        // Its strings are written as explicit string literals if they would be guessed otherwise and all other
+        // types as typed literals, so it reads back the same with or without guessing
+        assert_eq!(
+            sequence.to_string(),
+            r#"device == s"1m" && shift == s"12:00:00" && time(shift) > now && duration(s"90s") == d"1m 30s""#
+        );
+        assert_eq!(parse_tree(sequence.to_string())?, sequence);
+        assert_eq!(
+            parse_tree_with_options(sequence.to_string(), options, &functions)?,
+            sequence
+        );
+
+        let sequence = parse_tree(
+            r#"today == "2026-12-24" && "2026-12-24T18:00:00+01:00" > "2026-12-24T08:00:00.5Z" && now in ["08:30:00", "12:00:00.25"] && -"1h" < "90s""#,
+        )?;
+        assert_eq!(
+            sequence.to_string(),
+            r#"today == date"2026-12-24" && datetime"2026-12-24T18:00:00+01:00" > datetime"2026-12-24T08:00:00.500+00:00" && now in [t"08:30:00", t"12:00:00.250"] && -d"1h" < d"1m 30s""#
+        );
+        assert!(solve_tree(&sequence, &values)?);
+        assert_eq!(
+            parse_tree_with_options(sequence.to_string(), options, &functions)?,
+            sequence
+        );
+        assert!(matches!(
+            parse_tree(r#"a > date"2026-13-01""#),
+            Err(Error::Syntax { offset: 8, ref expected, .. }) if expected == &vec!["date"]
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_string_matching() -> Result<()> {
+        use crate::{
+            compare::{ComparisonType, Pattern},
+            evaluate, parse_tree,
+            sequence::Entity,
+            solve_tree, Error,
+        };
+
+        let mut values = HashMap::new();
+        values.insert("firmware", Value::from("v1.4.2-beta"));
+        values.insert("device", Value::from("kitchen.sensor.1h"));
+        values.insert("pattern", Value::from("^kitchen\\."));
+        values.insert("alarms", Value::from(vec!["smoke", "water"]));
+        values.insert("level", Value::Integer(2));
+        let values = MapResolver::from(values);
+
+        assert!(evaluate(
+            r#"firmware starts_with "v1." && firmware ends_with "-beta""#,
+            &values
+        )?);
+        assert!(evaluate(
+            r#"firmware contains "4.2" && !(firmware contains "4.3")"#,
+            &values
+        )?);
+        assert!(evaluate(r#"alarms contains "smoke" && "water" in alarms"#, &values)?);
+        // Strings to match against are not guessed to be a duration
+        assert!(evaluate(
+            r#"device ends_with "1h" && device contains "sensor.1h""#,
+            &values
+        )?);
+        // Strings to match against can be calculated
+        assert!(evaluate(
+            r#"device contains "sensor" + ".1h" && firmware =~ "^v" + "1\.""#,
+            &values
+        )?);
+        assert!(evaluate(r#"firmware =~ "^v1\.[0-9]+\.[0-9]+(-beta)?$""#, &values)?);
+        assert!(!evaluate(r#"firmware =~ "^v2""#, &values)?);
+        assert!(evaluate(r#"device =~ pattern && !(firmware =~ pattern)"#, &values)?);
+        assert!(matches!(
+            evaluate(r#"level =~ "2""#, &values),
+            Err(Error::IncompatibleTypes { .. })
+        ));
+        assert!(matches!(
+            evaluate(r#"level starts_with "2""#, &values),
+            Err(Error::IncompatibleTypes { .. })
+        ));
+        assert!(matches!(
+            evaluate(r#"firmware =~ "(v1""#, &values),
+            Err(Error::InvalidRegex(_))
+        ));
+
+        // The pattern is compiled once on parsing and kept in the sequence
+        let sequence = parse_tree(r#"firmware =~ "^v1\." || device ends_with "1h""#)?;
+        match &sequence.items[0] {
+            Entity::Comparison(comparison) => {
+                assert_eq!(comparison.against, ComparisonType::Pattern(Pattern::new("^v1\\.")?))
+            }
+            entity => panic!("Unexpected {:?}", entity),
+        }
+        assert!(solve_tree(&sequence, &values)?);
+        // Also within negations and blocks
+        let negated = parse_tree(r#"not firmware =~ "^v2" && (device == "x" || !(device =~ "^v"))"#)?;
+        match &negated.items[0] {
+            Entity::Not(entity) => assert!(matches!(
+                &**entity,
+                Entity::Comparison(comparison) if matches!(comparison.against, ComparisonType::Pattern(_))
+            )),
+            entity => panic!("Unexpected {:?}", entity),
+        }
+        assert!(solve_tree(&negated, &values)?);
+        assert!(matches!(
+            parse_tree(r#"!(firmware =~ "[v1")"#),
+            Err(Error::InvalidRegex(_))
+        ));
+        // A string which reads like a duration is written as explicit string literal
+        assert_eq!(sequence.to_string(), r#"firmware =~ "^v1\." || device ends_with s"1h""#);
+        assert_eq!(parse_tree(sequence.to_string())?, sequence);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_functions() -> Result<()> {
+        use chrono::{DateTime, NaiveTime};
+
+        use crate::{evaluate, explain, function::Arity, parse_tree, Error};
+
+        let mut values = HashMap::new();
+        values.insert("a", Value::Integer(3));
+        values.insert("b", Value::Numeric(6.5));
+        values.insert("t1", Value::Numeric(4.5));
+        values.insert("t2", Value::Integer(7));
+        values.insert("name", Value::from("Kitchen"));
+        values.insert("levels", Value::from(vec![3, 9, 1]));
+        values.insert("now", Value::from(NaiveTime::from_hms(8, 15, 0)));
+        values.insert(
+            "seen",
+            Value::from(DateTime::parse_from_rfc3339("2026-10-17T23:30:00+02:00")?),
+        );
+        values.insert("nothing", Value::Null);
+        let values = MapResolver::from(values);
+
+        assert!(evaluate(
+            "abs(a - b) > 2 && abs(-4) == 4 && abs(b - a) == 3.5",
+            &values
+        )?);
+        assert!(evaluate(
+            "min(t1, t2, 5) < 5 && max( t1 , t2 ) == 7 && max(a) == 3",
+            &values
+        )?);
+        assert!(evaluate(
+            "min(levels) == 1 && max(levels) == 9 && max([]) == null",
+            &values
+        )?);
+        assert!(evaluate(
+            r#"max("10:00:00", now) == "10:00:00" && min("1h", "5min") == "5min""#,
+            &values
+        )?);
+        assert!(evaluate(
+            "round(b) == 7 && floor(b) == 6 && ceil(t1) == 5 && round(a) == 3",
+            &values
+        )?);
+        assert!(evaluate("round(-2.5) == -3 && floor(-b) == -7", &values)?);
+        assert!(evaluate("len(name) == 7 && len(levels) == 3 && len([]) == 0", &values)?);
+        assert!(evaluate(
+            r#"lower(name) == "kitchen" && lower(name) starts_with "kit""#,
+            &values
+        )?);
+        assert!(evaluate(
+            "hour(now) >= 8 && hour(now) < 9 && hour(seen) == 23",
+            &values
+        )?);
+        // Calls are operands like any other and can be nested
+        assert!(evaluate(
+            "max(abs(a - b), 1) * 2 == 7 && round(min(t1, b) + 0.5) == 5",
+            &values
+        )?);
+        assert!(evaluate("abs(nothing) == null && max(a, nothing) == null", &values)?);
+        assert!(evaluate("abs(missing ?? -1) == 1", &values)?);
+
+        // Unknown functions and wrong numbers of arguments are rejected on parsing
+        assert_eq!(parse_tree("foo(a) > 1"), Err(Error::UnknownFunction("foo".into())));
+        assert_eq!(
+            parse_tree("a > 1 && !(abs(a, b) > 1)"),
+            Err(Error::WrongArity {
+                function: "abs".into(),
+                expected: Arity::Exactly(1),
+                found: 2
+            })
+        );
+        assert!(matches!(parse_tree("min() > 1"), Err(Error::WrongArity { .. })));
+        assert!(matches!(parse_tree("abs(a > 1"), Err(Error::Syntax { offset: 6, .. })));
         assert_eq!(
-            evaluate("a == 42 || ((b == 2 || b == 3) && (c == 3 || c == 4))", &values)?,
-            true
+            evaluate("lower(a) == 3", &values),
+            Err(Error::IncompatibleType {
+                op: "lower".into(),
+                value: "Integer"
+            })
         );
+        assert!(matches!(
+            evaluate("max(a, name) > 1", &values),
+            Err(Error::IncompatibleTypes { .. })
+        ));
+
+        let sequence = parse_tree("max(a, -b, (c + 1) * 2) > abs(d ?? 1)")?;
+        assert_eq!(sequence.to_string(), "max(a, -b, (c + 1) * 2) > abs(d ?? 1)");
+        assert_eq!(parse_tree(sequence.to_string())?, sequence);
+
         assert_eq!(
-            evaluate("a == 4711 || ((b == 42 || b == 2) && (c == 3 && c == 4))", &values)?,
-            false
+            explain("abs(a - b) > 2", &values)?.to_string(),
+            "\
+&& => true
+  abs(a - b) > 2 => true
+    a = 3
+    b = 6.5
+    a - b = -3.5
+    abs(a - b) = 3.5
+    3.5 > 2
+"
         );
 
-        assert_eq!(evaluate("((a == 1) && (b == 2))", &values)?, true);
-        assert_eq!(evaluate("((((a == 1))))", &values)?, true);
-        assert_eq!(evaluate("((((a == 1)))) && b == 2", &values)?, true);
-        assert_eq!(evaluate("(a == 1) && b == 3", &values)?, false);
-        assert_eq!(evaluate("b == 2 && (a == 1)", &values)?, true);
-        assert_eq!(evaluate("(b == 2) && (a == 1)", &values)?, true);
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_custom_functions() -> Result<()> {
+        use chrono::{Datelike, NaiveDate};
+
+        use crate::{
+            evaluate_with_functions, explain_tree,
+            function::{ArgumentType, Arity, FunctionRegistry, WithFunctions},
+            parse_tree, parse_tree_with_functions, solve_tree_tristate, Error, Tristate,
+        };
+
+        fn number(value: &Value) -> f64 {
+            match value {
+                Value::Numeric(value) => *value,
+                Value::Integer(value) => *value as f64,
+                Value::Unsigned(value) => *value as f64,
+                _ => unreachable!(),
+            }
+        }
+
+        let mut functions = FunctionRegistry::default();
+        functions
+            .register("dew_point", [ArgumentType::Number, ArgumentType::Number], |arguments| {
+                let (temperature, humidity) = (number(&arguments[0]), number(&arguments[1]));
+                Ok(Value::Numeric(temperature - (100.0 - humidity) / 5.0))
+            })
+            .register("is_holiday", [ArgumentType::Date], |arguments| match arguments {
+                [Value::Date(date)] => Ok(Value::Bool(date.month() == 12 && date.day() == 25)),
+                _ => unreachable!(),
+            })
+            .register("default", [ArgumentType::Any], |arguments| {
+                Ok(Value::Bool(arguments[0] == Value::Null))
+            })
+            // Custom functions shadow builtins of the same name
+            .register("len", [ArgumentType::List, ArgumentType::Number], |arguments| {
+                Ok(Value::Bool(
+                    matches!(arguments, [Value::List(items), Value::Integer(count)] if items.len() as i64 == *count),
+                ))
+            });
+
+        let mut values = HashMap::new();
+        values.insert("temperature", Value::Integer(20));
+        values.insert("humidity", Value::Numeric(80.0));
+        values.insert("today", Value::from(NaiveDate::from_ymd(2026, 12, 25)));
+        values.insert("levels", Value::from(vec![1, 2]));
+        values.insert("nothing", Value::Null);
+        let values = MapResolver::from(values);
+
+        assert!(evaluate_with_functions(
+            "dew_point(temperature, humidity) == 16 && dew_point(20, 100) > dew_point(20, 50)",
+            &values,
+            &functions
+        )?);
+        assert!(evaluate_with_functions(
+            r#"is_holiday(today) && !is_holiday("2026-12-24")"#,
+            &values,
+            &functions
+        )?);
+        // Builtins can still be called and nested with custom functions
+        assert!(evaluate_with_functions(
+            "round(dew_point(abs(-temperature), 81)) == 16",
+            &values,
+            &functions
+        )?);
+        assert!(evaluate_with_functions("len(levels, 2)", &values, &functions)?);
+        // Null arguments make the result null unless any value is accepted
+        assert!(evaluate_with_functions(
+            "is_holiday(nothing) == null && default(nothing)",
+            &values,
+            &functions
+        )?);
+
+        // Unknown functions and wrong numbers of arguments are rejected on parsing
+        assert!(parse_tree("dew_point(temperature, humidity) > 10").is_err());
+        assert_eq!(
+            parse_tree_with_functions("is_workday(today)", &functions),
+            Err(Error::UnknownFunction("is_workday".into()))
+        );
+        assert_eq!(
+            parse_tree_with_functions("a > 1 && (dew_point(temperature) > 10)", &functions),
+            Err(Error::WrongArity {
+                function: "dew_point".into(),
+                expected: Arity::Exactly(2),
+                found: 1
+            })
+        );
+        // Arguments of the wrong type are rejected on solving
+        assert_eq!(
+            evaluate_with_functions("is_holiday(temperature)", &values, &functions),
+            Err(Error::IncompatibleType {
+                op: "is_holiday".into(),
+                value: "Integer"
+            })
+        );
+
+        // All solvers call the custom functions with [WithFunctions]
+        let resolver = WithFunctions::new(&values, &functions);
+        let sequence = parse_tree_with_functions("is_holiday(today) || missing > 1", &functions)?;
+        assert_eq!(solve_tree_tristate(&sequence, &resolver)?, Tristate::True);
+        assert_eq!(solve_tree_with_functions(&sequence, &values, &functions), Ok(true));
+        assert_eq!(
+            explain_tree(
+                &parse_tree_with_functions("dew_point(temperature, 60) < 15", &functions)?,
+                &resolver
+            )?
+            .to_string(),
+            "\
+&& => true
+  dew_point(temperature, 60) < 15 => true
+    temperature = 20
+    dew_point(temperature, 60) = 12
+    12 < 15
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_between() -> Result<()> {
+        use chrono::{DateTime, NaiveTime};
+
+        use crate::{evaluate, explain, Error};
+
+        let mut values = HashMap::new();
+        values.insert("late", Value::from(NaiveTime::from_hms(23, 30, 0)));
+        values.insert("early", Value::from(NaiveTime::from_hms(5, 0, 0)));
+        values.insert("noon", Value::from(NaiveTime::from_hms(12, 0, 0)));
+        values.insert(
+            "now",
+            Value::from(DateTime::parse_from_rfc3339("2026-10-17T01:00:00+02:00")?),
+        );
+        values.insert("temp", Value::Numeric(21.5));
+        let values = MapResolver::from(values);
+
+        // Night windows wrap around midnight and include both ends
+        const NIGHT: &str = r#"between "22:00:00" and "06:00:00""#;
+        assert!(evaluate(format!("late {}", NIGHT), &values)?);
+        assert!(evaluate(format!("early {}", NIGHT), &values)?);
+        assert!(!evaluate(format!("noon {}", NIGHT), &values)?);
+        assert!(evaluate(
+            format!(r#""22:00:00" {0} && "06:00:00" {0}"#, NIGHT),
+            &values
+        )?);
+        assert!(!evaluate(format!(r#""06:00:01" {}"#, NIGHT), &values)?);
+        // A date-time is checked by its time of day at its own offset
+        assert!(evaluate(format!("now {}", NIGHT), &values)?);
+        assert!(evaluate(r#"!(now between "08:00:00" and "20:00:00")"#, &values)?);
+        // A window given by start and length wraps as well
+        assert!(evaluate(r#"early between late and late + "6h""#, &values)?);
+        assert!(evaluate(
+            r#"now between "2026-10-16T23:00:00Z" and "2026-10-17T00:00:00Z""#,
+            &values
+        )?);
+
+        assert!(evaluate(
+            "temp between 18 and 22 and temp + 1 between 20 and 25",
+            &values
+        )?);
+        assert!(!evaluate("temp between 22 and 18", &values)?);
+        assert!(evaluate(
+            "temp between 21.5 and 21.5 or missing between 1 and 2",
+            &values
+        )?);
+        assert!(matches!(
+            evaluate(format!("temp {}", NIGHT), &values),
+            Err(Error::IncompatibleTypes { .. })
+        ));
+        assert!(matches!(
+            evaluate("temp between 18", &values),
+            Err(Error::Syntax { expected, .. }) if expected.contains(&"and")
+        ));
+
+        assert_eq!(
+            explain(r#"late - "1h" between "22:00:00" and early"#, &values)?.to_string(),
+            "\
+&& => true
+  late - d\"1h\" between t\"22:00:00\" and early => true
+    late = 23:30:00
+    late - d\"1h\" = 22:30:00
+    early = 05:00:00
+    22:30:00 between 22:00:00 and 05:00:00
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_null() -> Result<()> {
+        use crate::{evaluate, Error};
+
+        let mut values = HashMap::new();
+        values.insert("a", Value::Numeric(1.0));
+        values.insert("reading", Value::Null);
+        values.insert("nullable", Value::from(None::<f64>));
+        let values = MapResolver::from(values);
+
+        assert!(evaluate("reading == null && nullable == null", &values)?);
+        assert!(evaluate("a != null && null == null", &values)?);
+        assert!(!evaluate(
+            r#"reading == 0 || reading == "null" || reading == false"#,
+            &values
+        )?);
+        assert!(evaluate("reading + 1 == null && -reading == null", &values)?);
+        assert!(evaluate("reading ?? 5 == 5 && a ?? 5 == 1", &values)?);
+        assert!(evaluate("exists(reading)", &values)?);
+        assert!(evaluate("!exists(null_ish)", &values)?);
+        assert_eq!(
+            evaluate("reading > 1", &values),
+            Err(Error::IncompatibleTypes {
+                op: ">".into(),
+                lhs: "Null",
+                rhs: "Integer"
+            })
+        );
+        assert_eq!(
+            evaluate("reading", &values),
+            Err(Error::UnexpectedType {
+                expected: "Bool",
+                found: "Null"
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain() -> Result<()> {
+        use crate::explain;
+
+        let mut values = HashMap::new();
+        values.insert("a", Value::Numeric(1.0));
+        values.insert("open", Value::Bool(true));
+        let values = MapResolver::from(values);
+
+        let explanation = explain("a == 4711 || !(-a > 0 && missing) && open", &values)?;
+        assert_eq!(explanation.result(), Some(true));
+        assert_eq!(
+            explanation.to_string(),
+            "\
+|| => true
+  a == 4711 => false
+    a = 1
+    1 == 4711
+  && => true
+    ! => true
+      && => false
+        -a > 0 => false
+          a = 1
+          -a = -1
+          -1 > 0
+        missing => short-circuited
+    open => true
+      open = true
+"
+        );
+        match explanation {
+            Explanation::Sequence { items, .. } => assert!(!items[0].is_short_circuited()),
+            explanation => panic!("Unexpected {:?}", explanation),
+        }
+        assert_eq!(
+            explain("missing", &values),
+            Err(crate::Error::UnresolvedVariable("missing".into()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_short_circuit() -> Result<()> {
+        use std::cell::RefCell;
+
+        use crate::evaluate;
+
+        /// Records all variable-names it has been asked for
+        struct RecordingResolver {
+            values: MapResolver,
+            resolved: RefCell<Vec<String>>,
+        }
+
+        impl Resolver for RecordingResolver {
+            fn resolve(&self, name: impl AsRef<str>) -> Option<&Value> {
+                self.resolved.borrow_mut().push(name.as_ref().into());
+                self.values.resolve(name)
+            }
+        }
+
+        let mut values = HashMap::new();
+        values.insert("a", 1);
+        values.insert("b", 2);
+        let values = RecordingResolver {
+            values: MapResolver::from(values),
+            resolved: RefCell::new(Vec::new()),
+        };
+
+        // Unresolvable variables in dead branches do not fail the evaluation
+        assert!(!evaluate("a == 2 && missing > 1", &values)?);
+        assert!(evaluate("a == 1 || missing > 1", &values)?);
+        assert!(evaluate("(a == 2 && missing > 1) || b == 2", &values)?);
+        assert!(evaluate("b == 2 || (a == 1 && missing > 1) && missing", &values)?);
+        assert!(!evaluate("!(a == 1) && missing", &values)?);
+        assert!(evaluate("a == 1 && missing > 1", &values).is_err());
+
+        values.resolved.borrow_mut().clear();
+        assert!(!evaluate("a == 2 && (b == 2 || b == 3)", &values)?);
+        assert_eq!(*values.resolved.borrow(), vec!["a".to_string()]);
+
+        values.resolved.borrow_mut().clear();
+        assert!(evaluate("a == 1 || b == 2 && b == 3", &values)?);
+        assert_eq!(*values.resolved.borrow(), vec!["a".to_string()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve_errors() -> Result<()> {
+        use crate::{evaluate, Error};
+
+        let mut values = HashMap::new();
+        values.insert("a", Value::Numeric(1.0));
+        values.insert("d", Value::from(core::time::Duration::from_secs(60)));
+        let values = MapResolver::from(values);
+
+        assert_eq!(
+            evaluate("a == 1 && missing > 1", &values),
+            Err(Error::UnresolvedVariable("missing".into()))
+        );
+        assert_eq!(
+            evaluate(r#"a + "foo" > 1"#, &values),
+            Err(Error::IncompatibleTypes {
+                op: "+".into(),
+                lhs: "Numeric",
+                rhs: "String"
+            })
+        );
+        assert_eq!(
+            evaluate(r#"-"foo" == 1"#, &values),
+            Err(Error::IncompatibleType {
+                op: "-".into(),
+                value: "String"
+            })
+        );
+        assert_eq!(
+            evaluate(r#"a == "08:00:00""#, &values),
+            Err(Error::IncompatibleTypes {
+                op: "==".into(),
+                lhs: "Numeric",
+                rhs: "Time"
+            })
+        );
+        assert!(evaluate("d > 1", &values).is_err());
+        assert_eq!(evaluate("a / 0 > 1", &values), Err(Error::DivisionByZero));
+        assert_eq!(evaluate("d / 0 > 1", &values), Err(Error::DivisionByZero));
+        assert_eq!(
+            evaluate("a", &values),
+            Err(Error::UnexpectedType {
+                expected: "Bool",
+                found: "Numeric"
+            })
+        );
+        assert!(matches!(
+            evaluate("a >", &values),
+            Err(Error::Syntax {
+                offset: 3,
+                line: 1,
+                column: 4,
+                ..
+            })
+        ));
+        assert_eq!(
+            evaluate("a == 1 || (b > 1 && c ==)", &values)
+                .unwrap_err()
+                .render("a == 1 || (b > 1 && c ==)"),
+            "Syntax error at line 1, column 25: expected -, value, variable or (\n\
+             a == 1 || (b > 1 && c ==)\n\
+             \x20                       ^"
+        );
 
         Ok(())
     }
@@ -0,0 +1,69 @@
+use std::{fmt::Display, ops::Not};
+
+/// Result of a three-valued (Kleene) solving where an unresolvable variable is [Tristate::Unknown] instead of an
+/// [crate::Error]. A decided [crate::Sequence] stays decided, so `false && missing` is [Tristate::False] and
+/// `true || missing` is [Tristate::True], while `true && missing` is [Tristate::Unknown].
+/// ```
+/// use std::collections::HashMap;
+///
+/// use metrics_evaluation::*;
+///
+/// let mut values = HashMap::new();
+/// values.insert("a", 1);
+/// let values: MapResolver = values.into();
+///
+/// assert_eq!(evaluate_tristate("a == 1 || missing > 1", &values), Ok(Tristate::True));
+/// assert_eq!(evaluate_tristate("a == 2 && missing > 1", &values), Ok(Tristate::False));
+/// assert_eq!(evaluate_tristate("a == 1 && missing > 1", &values), Ok(Tristate::Unknown));
+/// assert_eq!(evaluate_tristate("!(missing > 1)", &values), Ok(Tristate::Unknown));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tristate {
+    True,
+    False,
+    /// The result can not be told as a variable could not be resolved
+    Unknown,
+}
+
+impl Tristate {
+    /// The [bool] of a known result or [None] if [Tristate::Unknown]
+    pub fn known(self) -> Option<bool> {
+        match self {
+            Self::True => Some(true),
+            Self::False => Some(false),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl From<bool> for Tristate {
+    fn from(value: bool) -> Self {
+        if value {
+            Self::True
+        } else {
+            Self::False
+        }
+    }
+}
+
+impl Not for Tristate {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Self::True => Self::False,
+            Self::False => Self::True,
+            Self::Unknown => Self::Unknown,
+        }
+    }
+}
+
+impl Display for Tristate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::True => write!(f, "true"),
+            Self::False => write!(f, "false"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
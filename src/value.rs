@@ -1,64 +1,366 @@
 use std::{
+    cmp::Ordering,
     fmt::Display,
-    ops::{Add, Div, Mul, Sub},
-    time::Duration,
+    ops::{Add, Div, Mul, Neg, Rem, Sub},
 };
 
-use anyhow::{bail, Result};
-use chrono::naive::NaiveTime;
+use chrono::{
+    naive::{NaiveDate, NaiveDateTime, NaiveTime},
+    DateTime, Duration, FixedOffset, Utc,
+};
 #[cfg(feature = "serde_de")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
     calculate::{Arithmetic, Calculateable},
-    compare::{Compareable, Operator},
+    compare::{Compareable, Operator, Pattern},
+    error::{Error, Result},
 };
 
 /// Representation of different value types for use in comparisons.
 /// Is [Compareable] and [Calculateable].
+/// [Value::Numeric], [Value::Integer] and [Value::Unsigned] are all numbers which can be compared and calculated
+/// with each other.
 #[cfg_attr(feature = "serde_de", derive(Deserialize, Serialize))]
-#[cfg_attr(not(feature = "lax_comparison"), derive(PartialEq))]
-#[derive(Debug, PartialOrd, Clone)]
+#[derive(Debug, Clone)]
 pub enum Value {
     /// A string-value which represents any kind of text sequence
     String(String),
     /// A numeric value which has u8 to f64 conversions implemented
     Numeric(f64),
+    /// An exact signed integer (e.G. a counter or a timestamp in nanoseconds)
+    Integer(i64),
+    /// An exact unsigned integer for values above [i64::MAX]
+    Unsigned(u64),
     /// A simple [bool] wrapper
     Bool(bool),
     /// A time-representation
     Time(NaiveTime),
-    /// A duration in time
+    /// A calendar date without time
+    Date(NaiveDate),
+    /// A point in time with its timezone-offset
+    DateTime(DateTime<FixedOffset>),
+    /// A signed duration in time, e.G. the difference of two [Value::Time]s which may be negative
+    #[cfg_attr(feature = "serde_de", serde(with = "serde_duration"))]
     Duration(Duration),
+    /// An explicitly reported "no value" which is only equal to another [Value::Null]
+    Null,
+    /// A list of values (e.G. a set of active alarms) to look for a value `in`
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Name of the type of this [Value] as used in [Error]s
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "String",
+            Self::Numeric(_) => "Numeric",
+            Self::Integer(_) => "Integer",
+            Self::Unsigned(_) => "Unsigned",
+            Self::Bool(_) => "Bool",
+            Self::Time(_) => "Time",
+            Self::Date(_) => "Date",
+            Self::DateTime(_) => "DateTime",
+            Self::Duration(_) => "Duration",
+            Self::Null => "Null",
+            Self::List(_) => "List",
+        }
+    }
+
+    /// Whether `self` lies within the range from `from` to `to` including both ends.
+    /// A range of [Value::Time]s where `from` is later than `to` wraps around midnight, so it contains everything
+    /// from `from` until midnight and from midnight until `to`. A [Value::DateTime] is within a range of
+    /// [Value::Time]s if its time of day (at its own offset) is. Any other range is empty if `from` is greater than `to`.
+    ///
+    /// As [Value::Time] + [Value::Duration] wraps around midnight as well, a window can be given by its start and
+    /// length (`now between start and start + "8h"`).
+    /// ```
+    /// use chrono::{DateTime, NaiveTime};
+    /// use metrics_evaluation::*;
+    ///
+    /// let night = (Value::from(NaiveTime::from_hms(22, 0, 0)), Value::from(NaiveTime::from_hms(6, 0, 0)));
+    /// assert_eq!(Value::from(NaiveTime::from_hms(23, 30, 0)).between(&night.0, &night.1), Ok(true));
+    /// assert_eq!(Value::from(NaiveTime::from_hms(5, 59, 59)).between(&night.0, &night.1), Ok(true));
+    /// assert_eq!(Value::from(NaiveTime::from_hms(12, 0, 0)).between(&night.0, &night.1), Ok(false));
+    ///
+    /// let now = Value::from(DateTime::parse_from_rfc3339("2026-10-17T23:00:00+02:00").unwrap());
+    /// assert_eq!(now.between(&night.0, &night.1), Ok(true));
+    ///
+    /// assert_eq!(Value::Integer(3).between(&Value::Integer(1), &Value::Numeric(3.0)), Ok(true));
+    /// assert_eq!(Value::Integer(3).between(&Value::Integer(5), &Value::Integer(1)), Ok(false));
+    /// assert!(Value::Integer(3).between(&night.0, &night.1).is_err());
+    /// ```
+    pub fn between(&self, from: &Value, to: &Value) -> Result<bool> {
+        match (self, from, to) {
+            (Value::DateTime(value), Value::Time(_), Value::Time(_)) => Value::Time(value.time()).between(from, to),
+            (Value::Time(_), Value::Time(_), Value::Time(_)) if from > to => {
+                Ok(self.compare(from, Operator::GreaterEqual)? || self.compare(to, Operator::LessEqual)?)
+            }
+            _ => Ok(self.compare(from, Operator::GreaterEqual)? && self.compare(to, Operator::LessEqual)?),
+        }
+    }
+
+    /// Write `self` the way it is written in an expression, e.G. with quotation marks for a [Value::String]
+    pub(crate) fn fmt_literal(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            // A string which would be guessed to be of another type is written as explicit string literal
+            Self::String(s) if Value::guess_typed(s).is_some() => {
+                write!(f, "s\"{}\"", EscapeString(s))
+            }
+            Self::String(s) => write!(f, "\"{}\"", EscapeString(s)),
+            Self::Numeric(d) => write!(f, "{}", d),
+            Self::Integer(i) => write!(f, "{}", i),
+            Self::Unsigned(u) => write!(f, "{}", u),
+            Self::Bool(b) => write!(f, "{}", b),
+            // Typed literals read back the same whether literals are guessed or not
+            Self::Time(t) => write!(f, "t\"{}\"", t),
+            Self::Date(d) => write!(f, "date\"{}\"", d),
+            Self::DateTime(d) => write!(f, "datetime\"{}\"", d.to_rfc3339()),
+            // A negative duration is written as negation as there is no literal for it
+            Self::Duration(d) if *d < Duration::zero() => write!(f, "-d\"{}\"", FormatDuration(&-*d)),
+            Self::Duration(d) => write!(f, "d\"{}\"", FormatDuration(d)),
+            Self::Null => write!(f, "null"),
+            Self::List(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.fmt_literal(f)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+
+    /// Parse a number the way it is written in an expression: integers are [Value::Integer] (or [Value::Unsigned]
+    /// above [i64::MAX]), anything else that is a valid [f64] is [Value::Numeric].
+    pub(crate) fn parse_number(text: &str) -> Option<Value> {
+        str::parse::<i64>(text)
+            .map(Value::Integer)
+            .or_else(|_| str::parse::<u64>(text).map(Value::Unsigned))
+            .or_else(|_| str::parse::<f64>(text).map(Value::Numeric))
+            .ok()
+    }
+
+    /// Parse a [Value::Duration] in [humantime] format like `5min`
+    pub(crate) fn parse_duration(text: &str) -> Option<Value> {
+        humantime::parse_duration(text)
+            .ok()
+            .and_then(|duration| Value::try_from_std(duration).ok())
+    }
+
+    /// Convert a [std::time::Duration] into a [Value::Duration], which is [Error::OutOfRange] beyond the range of a
+    /// [Duration] (about 292 million years) instead of saturating like [From] does
+    /// ```
+    /// use metrics_evaluation::*;
+    ///
+    /// assert_eq!(Value::try_from_std(std::time::Duration::from_secs(90)), Ok(Value::from(chrono::Duration::seconds(90))));
+    /// assert!(matches!(Value::try_from_std(std::time::Duration::MAX), Err(Error::OutOfRange(_))));
+    /// ```
+    pub fn try_from_std(duration: std::time::Duration) -> Result<Value> {
+        Duration::from_std(duration)
+            .map(Value::Duration)
+            .map_err(|_| Error::OutOfRange(format!("{:?} exceeds the supported range of a duration", duration)))
+    }
+
+    /// Parse a [Value::Time] in the form of `%H:%M:%S` with optional fraction of a second
+    pub(crate) fn parse_time(text: &str) -> Option<Value> {
+        NaiveTime::parse_from_str(text, "%H:%M:%S%.f").ok().map(Value::from)
+    }
+
+    /// Parse a [Value::Date] in the form of `%Y-%m-%d`
+    pub(crate) fn parse_date(text: &str) -> Option<Value> {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").ok().map(Value::from)
+    }
+
+    /// Parse a [Value::DateTime] in RFC 3339 format, which is UTC if the offset is missing
+    pub(crate) fn parse_date_time(text: &str) -> Option<Value> {
+        DateTime::parse_from_rfc3339(text)
+            .map(Value::from)
+            .or_else(|_| {
+                NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f")
+                    .map(|naive| Value::from(DateTime::<Utc>::from_utc(naive, Utc)))
+            })
+            .ok()
+    }
+
+    /// Guess the type of a string literal: a `text` that reads like a [Value::Duration], [Value::Time], [Value::Date]
+    /// or [Value::DateTime] is one, anything else a [Value::String]
+    pub(crate) fn guess(text: String) -> Value {
+        Value::guess_typed(&text).unwrap_or(Value::String(text))
+    }
+
+    /// The [Value] other than a [Value::String] which [Value::guess] guesses `text` to be
+    fn guess_typed(text: &str) -> Option<Value> {
+        // All guessed types start with a digit (or the sign of a year), so most strings are not parsed at all
+        if !text
+            .trim_start()
+            .starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-')
+        {
+            return None;
+        }
+        Value::parse_duration(text)
+            .or_else(|| Value::parse_time(text))
+            .or_else(|| Value::parse_date(text))
+            .or_else(|| Value::parse_date_time(text))
+    }
+
+    /// Whether this is a [Value::Numeric], [Value::Integer] or [Value::Unsigned]
+    pub(crate) fn is_number(&self) -> bool {
+        matches!(self, Self::Numeric(_) | Self::Integer(_) | Self::Unsigned(_))
+    }
+
+    /// The exact value of an integer
+    fn as_integer(&self) -> Option<i128> {
+        match self {
+            Self::Integer(value) => Some(*value as i128),
+            Self::Unsigned(value) => Some(*value as i128),
+            _ => None,
+        }
+    }
+
+    /// The (possibly rounded) [f64] of any number
+    pub(crate) fn as_float(&self) -> Option<f64> {
+        match self {
+            Self::Numeric(value) => Some(*value),
+            Self::Integer(value) => Some(*value as f64),
+            Self::Unsigned(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    /// The integer `value` as [Value::Integer] or [Value::Unsigned] if it exceeds [i64]
+    pub(crate) fn from_integer(value: i128) -> Result<Value> {
+        i64::try_from(value)
+            .map(Value::Integer)
+            .or_else(|_| u64::try_from(value).map(Value::Unsigned))
+            .map_err(|_| Error::OutOfRange(format!("{} exceeds the range of integers", value)))
+    }
+
+    /// Equality of `self` and `other` or [None] if both can not be compared at all.
+    /// With feature `lax_comparison`, some conversions are tried for different types.
+    fn equals(&self, other: &Self) -> Option<bool> {
+        match (self, other) {
+            (Value::String(left), Value::String(right)) => Some(left == right),
+            (Value::Bool(left), Value::Bool(right)) => Some(left == right),
+            (Value::Time(left), Value::Time(right)) => Some(left == right),
+            (Value::Date(left), Value::Date(right)) => Some(left == right),
+            (Value::DateTime(left), Value::DateTime(right)) => Some(left == right),
+            (Value::Duration(left), Value::Duration(right)) => Some(left == right),
+            (Value::List(left), Value::List(right)) => Some(left == right),
+            (left, right) if left.is_number() && right.is_number() => {
+                Some(compare_numbers(left, right) == Some(Ordering::Equal))
+            }
+            // Null is only equal to itself but can be checked against anything
+            (Value::Null, Value::Null) => Some(true),
+            (Value::Null, _) | (_, Value::Null) => Some(false),
+            #[cfg(feature = "lax_comparison")]
+            (left, right) => lax_equals(left, right),
+            #[cfg(not(feature = "lax_comparison"))]
+            _ => None,
+        }
+    }
+
+    /// Ordering of `self` and `other` or [None] if both are of different types
+    fn ordering(&self, other: &Self) -> Option<Option<Ordering>> {
+        match (self, other) {
+            (Value::String(left), Value::String(right)) => Some(left.partial_cmp(right)),
+            (Value::Bool(left), Value::Bool(right)) => Some(left.partial_cmp(right)),
+            (Value::Time(left), Value::Time(right)) => Some(left.partial_cmp(right)),
+            (Value::Date(left), Value::Date(right)) => Some(left.partial_cmp(right)),
+            (Value::DateTime(left), Value::DateTime(right)) => Some(left.partial_cmp(right)),
+            (Value::Duration(left), Value::Duration(right)) => Some(left.partial_cmp(right)),
+            (left, right) if left.is_number() && right.is_number() => Some(compare_numbers(left, right)),
+            _ => None,
+        }
+    }
+}
+
+/// Exact ordering of two numbers, also if an integer can not be represented as [f64] without rounding
+fn compare_numbers(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left.as_integer(), right.as_integer()) {
+        (Some(left), Some(right)) => Some(left.cmp(&right)),
+        (Some(left), None) => compare_integer_float(left, right.as_float()?),
+        (None, Some(right)) => compare_integer_float(right, left.as_float()?).map(Ordering::reverse),
+        (None, None) => left.as_float()?.partial_cmp(&right.as_float()?),
+    }
+}
+
+fn compare_integer_float(integer: i128, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        return None;
+    }
+    // Compare the integral part exactly and let the fraction decide on a tie
+    let integral = float.trunc();
+    if integral >= i128::MAX as f64 {
+        return Some(Ordering::Less);
+    }
+    if integral < i128::MIN as f64 {
+        return Some(Ordering::Greater);
+    }
+    Some(
+        integer
+            .cmp(&(integral as i128))
+            .then(0f64.partial_cmp(&(float - integral))?),
+    )
 }
 
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match &self {
-            &Self::String(s) => write!(f, "{}", s),
-            &Self::Numeric(d) => write!(f, "{}", d),
-            &Self::Bool(b) => write!(f, "{}", b),
-            &Self::Time(t) => write!(f, "{}", t),
-            &Self::Duration(d) => write!(f, "{:?}", d),
+        match self {
+            Self::String(s) => write!(f, "{}", s),
+            Self::Numeric(d) => write!(f, "{}", d),
+            Self::Integer(i) => write!(f, "{}", i),
+            Self::Unsigned(u) => write!(f, "{}", u),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Time(t) => write!(f, "{}", t),
+            Self::Date(d) => write!(f, "{}", d),
+            Self::DateTime(d) => write!(f, "{}", d.to_rfc3339()),
+            Self::Duration(d) => write!(f, "{}", FormatDuration(d)),
+            Self::Null => write!(f, "null"),
+            Self::List(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
         }
     }
 }
 
-#[cfg(feature = "lax_comparison")]
-/// Extra lax comparison tries to perform some conversions on compare and may succeed where std [PartialEq] fails
+/// Values are equal like in a comparison with `==`, so numbers are equal by their value regardless of being
+/// [Value::Numeric], [Value::Integer] or [Value::Unsigned]. Values which can not be compared are never equal.
+///
+/// With feature `lax_comparison`, this tries to perform some conversions and may succeed where a strict comparison fails
 /// ```
 /// use metrics_evaluation::*;
 ///
+/// assert_eq!(&Value::Integer(1), &Value::Numeric(1.0));
+/// assert_ne!(&Value::Integer(i64::MAX), &Value::Numeric(i64::MAX as f64));
+/// assert_eq!(&Value::Unsigned(42), &Value::Integer(42));
+/// assert_ne!(&Value::Numeric(1.0), &Value::Duration(chrono::Duration::seconds(1)));
+/// assert_ne!(&Value::Numeric(0.0), &Value::Null);
+/// assert_eq!(&Value::Null, &Value::Null);
+///
+/// # #[cfg(feature = "lax_comparison")]
+/// # {
 /// // Numeric vs Bool: anything != 0 is true
 /// assert_eq!(&Value::Numeric(1.0), &Value::Bool(true));
 /// assert_eq!(&Value::Numeric(2.0), &Value::Bool(true));
 /// assert_eq!(&Value::Numeric(0.0), &Value::Bool(false));
 /// assert_eq!(&Value::Bool(false), &Value::Numeric(0.0));
+/// assert_eq!(&Value::Bool(true), &Value::Integer(7));
 ///
 /// // Numeric vs. String
 /// assert_eq!(&Value::String("1".into()), &Value::Numeric(1.0));
 /// assert_eq!(&Value::Numeric(42.0), &Value::String("42".into()));
 /// assert_ne!(&Value::Numeric(123.0), &Value::String("foo".into()));
+/// assert_eq!(&Value::String("18446744073709551615".into()), &Value::Unsigned(u64::MAX));
 ///
 /// // Bool vs. String
 /// assert_eq!(&Value::String("true".into()), &Value::Bool(true));
@@ -67,40 +369,101 @@ impl Display for Value {
 /// assert_ne!(&Value::String("false".into()), &Value::Bool(true));
 /// assert_ne!(&Value::String("foo".into()), &Value::Bool(true));
 /// assert_ne!(&Value::String("foo".into()), &Value::Bool(false));
+/// # }
 /// ```
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            // Same types - this equals the behavior of [PartialEq]
-            (Value::String(left), Value::String(right)) => left == right,
-            (Value::Numeric(left), Value::Numeric(right)) => left == right,
-            (Value::Bool(left), Value::Bool(right)) => left == right,
-            (Value::Time(left), Value::Time(right)) => left == right,
-            (Value::Duration(left), Value::Duration(right)) => left == right,
-            // Implicit conversion: bool vs. numeric where numeric != 0 == true
-            (Value::Bool(left), Value::Numeric(right)) => left == &(right != &0f64),
-            (Value::Numeric(left), Value::Bool(right)) => &(left != &0f64) == right,
-            // Implicit conversion: string vs. numeric where the string must be parseable as `f64`
-            (Value::String(left), Value::Numeric(right)) => str::parse::<f64>(left).ok().as_ref() == Some(right),
-            (Value::Numeric(left), Value::String(right)) => str::parse::<f64>(right).ok().as_ref() == Some(left),
-            // Implicit conversion: string vs. bool where a string is the `display` of the bool
-            (Value::String(left), Value::Bool(right)) => left == &format!("{}", right),
-            (Value::Bool(left), Value::String(right)) => &format!("{}", left) == right,
-            _ => panic!("Unable to compare {:?} against {:?}", self, other),
-        }
+        // Anything that can not be compared is not equal
+        self.equals(other).unwrap_or(false)
+    }
+}
+
+/// Values are ordered like in a comparison, values which can not be ordered have no ordering
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.ordering(other).flatten()
     }
 }
 
+#[cfg(feature = "lax_comparison")]
+/// Equality of different types by implicit conversions
+fn lax_equals(left: &Value, right: &Value) -> Option<bool> {
+    match (left, right) {
+        // Implicit conversion: bool vs. numeric where numeric != 0 == true
+        (Value::Bool(left), right) if right.is_number() => Some(*left == (right.as_float() != Some(0f64))),
+        (left, Value::Bool(right)) if left.is_number() => Some((left.as_float() != Some(0f64)) == *right),
+        // Implicit conversion: string vs. numeric where the string must be parseable as number
+        (Value::String(left), right) if right.is_number() => Some(Value::parse_number(left).as_ref() == Some(right)),
+        (left, Value::String(right)) if left.is_number() => Some(Value::parse_number(right).as_ref() == Some(left)),
+        // Implicit conversion: string vs. bool where a string is the `display` of the bool
+        (Value::String(left), Value::Bool(right)) => Some(left == &format!("{}", right)),
+        (Value::Bool(left), Value::String(right)) => Some(&format!("{}", left) == right),
+        _ => None,
+    }
+}
+
+/// Values of different types can not be compared and fail, unless the feature `lax_comparison` knows a conversion
+/// for an (in-)equality. Ordering of different types always fails.
+/// ```
+/// use metrics_evaluation::{
+///     compare::{Compareable, Operator},
+///     *,
+/// };
+///
+/// assert_eq!(Value::Numeric(1.0).compare(&Value::Numeric(2.0), Operator::Less), Ok(true));
+/// assert_eq!(
+///     Value::Numeric(1.0).compare(&Value::Duration(chrono::Duration::seconds(1)), Operator::Equal),
+///     Err(Error::IncompatibleTypes {
+///         op: "==".into(),
+///         lhs: "Numeric",
+///         rhs: "Duration"
+///     })
+/// );
+/// assert!(Value::Bool(true).compare(&Value::Numeric(1.0), Operator::Greater).is_err());
+/// ```
 impl Compareable for Value {
-    fn compare(&self, other: &Self, operator: Operator) -> bool {
-        match operator {
-            Operator::Equal => self == other,
-            Operator::NotEqual => self != other,
-            Operator::Greater => self > other,
-            Operator::Less => self < other,
-            Operator::GreaterEqual => self >= other,
-            Operator::LessEqual => self <= other,
-        }
+    fn compare(&self, other: &Self, operator: Operator) -> Result<bool> {
+        let incompatible = || Error::incompatible_types(operator, self, other);
+        let result = match operator {
+            Operator::Equal => self.equals(other).ok_or_else(incompatible)?,
+            Operator::NotEqual => !self.equals(other).ok_or_else(incompatible)?,
+            Operator::In | Operator::NotIn => match other {
+                // Items of another type are simply not equal, so lists may mix types
+                Value::List(items) => items.contains(self) == (operator == Operator::In),
+                _ => return Err(incompatible()),
+            },
+            Operator::Contains => match (self, other) {
+                (Value::String(text), Value::String(part)) => text.contains(part.as_str()),
+                (Value::List(items), item) => items.contains(item),
+                _ => return Err(incompatible()),
+            },
+            Operator::StartsWith => match (self, other) {
+                (Value::String(text), Value::String(part)) => text.starts_with(part.as_str()),
+                _ => return Err(incompatible()),
+            },
+            Operator::EndsWith => match (self, other) {
+                (Value::String(text), Value::String(part)) => text.ends_with(part.as_str()),
+                _ => return Err(incompatible()),
+            },
+            // A pattern which is not known on parsing (e.G. from a variable) is compiled on every comparison
+            Operator::Matches => match other {
+                Value::String(pattern) => Pattern::new(pattern)?.is_match(self)?,
+                _ => return Err(incompatible()),
+            },
+            _ => match self.ordering(other).ok_or_else(incompatible)? {
+                // Not comparable values of the same type (NaN) are neither greater nor less
+                None => false,
+                Some(ordering) => match operator {
+                    Operator::Greater => ordering == Ordering::Greater,
+                    Operator::Less => ordering == Ordering::Less,
+                    Operator::GreaterEqual => ordering != Ordering::Less,
+                    Operator::LessEqual => ordering != Ordering::Greater,
+                    _ => unreachable!(),
+                },
+            },
+        };
+
+        Ok(result)
     }
 }
 
@@ -130,6 +493,31 @@ impl Compareable for Value {
 ///     .calculate(&Value::Numeric(2.0), Arithmetic::Div)
 ///     .unwrap();
 /// assert_eq!(bar, Value::Numeric(2.0));
+///
+/// // Integers are calculated exactly and checked for overflows
+/// let foo = Value::Integer(i64::MAX);
+/// let bar = foo
+///     .calculate(&Value::Integer(1), Arithmetic::Add)
+///     .unwrap();
+/// assert!(matches!(bar, Value::Unsigned(9223372036854775808)));
+/// assert!(Value::Unsigned(u64::MAX)
+///     .calculate(&Value::Integer(1), Arithmetic::Add)
+///     .is_err());
+/// // An integer division stays an integer only if there is no remainder
+/// assert!(matches!(Value::Integer(6).calculate(&Value::Integer(3), Arithmetic::Div), Ok(Value::Integer(2))));
+/// assert!(matches!(Value::Integer(7).calculate(&Value::Integer(2), Arithmetic::Div), Ok(Value::Numeric(n)) if n == 3.5));
+/// // Remainders and integer divisions are euclidean, so the remainder is never negative
+/// assert_eq!(Value::Integer(-7).calculate(&Value::Integer(3), Arithmetic::Rem), Ok(Value::Integer(2)));
+/// assert_eq!(Value::Integer(-7).calculate(&Value::Integer(3), Arithmetic::IntDiv), Ok(Value::Integer(-3)));
+/// assert_eq!(Value::Integer(2).calculate(&Value::Integer(10), Arithmetic::Pow), Ok(Value::Integer(1024)));
+/// assert_eq!(Value::Integer(2).calculate(&Value::Integer(-1), Arithmetic::Pow), Ok(Value::Numeric(0.5)));
+///
+/// // Null propagates through any calculation
+/// let foo = Value::Null;
+/// let bar = foo
+///     .calculate(&Value::Numeric(2.0), Arithmetic::Mul)
+///     .unwrap();
+/// assert_eq!(bar, Value::Null);
 /// ```
 impl Calculateable for Value {
     fn calculate(self, value: &Self, arithmetic: Arithmetic) -> Result<Self> {
@@ -138,6 +526,9 @@ impl Calculateable for Value {
             Arithmetic::Sub => self - value,
             Arithmetic::Mul => self * value,
             Arithmetic::Div => self / value,
+            Arithmetic::Rem => self % value,
+            Arithmetic::Pow => self.pow(value),
+            Arithmetic::IntDiv => self.div_euclid(value),
         }
     }
 }
@@ -147,14 +538,24 @@ impl Add<&Self> for Value {
 
     fn add(self, other: &Self) -> Self::Output {
         match (self, other) {
-            (Value::String(lhs), Value::String(rhs)) => Ok(Value::String(lhs + &rhs)),
-            (Value::String(lhs), Value::Numeric(rhs)) => Ok(Value::String(format!("{} {}", lhs, rhs))),
-            (Value::Numeric(lhs), Value::Numeric(rhs)) => Ok(Value::Numeric(lhs + rhs)),
-            (Value::Duration(lhs), Value::Duration(rhs)) => Ok(Value::Duration(lhs + *rhs)),
-            (Value::Time(lhs), Value::Duration(rhs)) => Ok(Value::Time(
-                lhs + chrono::Duration::from_std(rhs.clone()).expect("Unable to convert duration"),
-            )),
-            _ => bail!("Incompatible types for addition"),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (Value::String(lhs), Value::String(rhs)) => Ok(Value::String(lhs + rhs)),
+            (Value::String(lhs), rhs) if rhs.is_number() => Ok(Value::String(format!("{} {}", lhs, rhs))),
+            (lhs, rhs) if lhs.is_number() && rhs.is_number() => calculate_numbers(&lhs, rhs, Arithmetic::Add),
+            (Value::Duration(lhs), Value::Duration(rhs)) => lhs
+                .checked_add(rhs)
+                .map(Value::Duration)
+                .ok_or_else(|| out_of_range(Arithmetic::Add, &FormatDuration(&lhs), &FormatDuration(rhs))),
+            (Value::Time(lhs), Value::Duration(rhs)) => Ok(Value::Time(lhs + *rhs)),
+            (Value::Date(lhs), Value::Duration(rhs)) => lhs
+                .checked_add_signed(*rhs)
+                .map(Value::Date)
+                .ok_or_else(|| out_of_range(Arithmetic::Add, &lhs, &FormatDuration(rhs))),
+            (Value::DateTime(lhs), Value::Duration(rhs)) => lhs
+                .checked_add_signed(*rhs)
+                .map(Value::DateTime)
+                .ok_or_else(|| out_of_range(Arithmetic::Add, &lhs, &FormatDuration(rhs))),
+            (lhs, rhs) => Err(Error::incompatible_types(Arithmetic::Add, &lhs, rhs)),
         }
     }
 }
@@ -164,12 +565,26 @@ impl Sub<&Self> for Value {
 
     fn sub(self, other: &Self) -> Self::Output {
         match (self, other) {
-            (Value::Numeric(lhs), Value::Numeric(rhs)) => Ok(Value::Numeric(lhs - rhs)),
-            (Value::Duration(lhs), Value::Duration(rhs)) => Ok(Value::Duration(lhs - *rhs)),
-            (Value::Time(lhs), Value::Duration(rhs)) => Ok(Value::Time(
-                lhs - chrono::Duration::from_std(rhs.clone()).expect("Unable to convert duration"),
-            )),
-            _ => bail!("Incompatible types for substraction"),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (lhs, rhs) if lhs.is_number() && rhs.is_number() => calculate_numbers(&lhs, rhs, Arithmetic::Sub),
+            (Value::Duration(lhs), Value::Duration(rhs)) => lhs
+                .checked_sub(rhs)
+                .map(Value::Duration)
+                .ok_or_else(|| out_of_range(Arithmetic::Sub, &FormatDuration(&lhs), &FormatDuration(rhs))),
+            (Value::Time(lhs), Value::Duration(rhs)) => Ok(Value::Time(lhs - *rhs)),
+            (Value::Date(lhs), Value::Duration(rhs)) => lhs
+                .checked_sub_signed(*rhs)
+                .map(Value::Date)
+                .ok_or_else(|| out_of_range(Arithmetic::Sub, &lhs, &FormatDuration(rhs))),
+            (Value::DateTime(lhs), Value::Duration(rhs)) => lhs
+                .checked_sub_signed(*rhs)
+                .map(Value::DateTime)
+                .ok_or_else(|| out_of_range(Arithmetic::Sub, &lhs, &FormatDuration(rhs))),
+            // Differences of points in time are negative if `rhs` is later than `lhs`
+            (Value::Time(lhs), Value::Time(rhs)) => Ok(Value::Duration(lhs - *rhs)),
+            (Value::Date(lhs), Value::Date(rhs)) => Ok(Value::Duration(lhs - *rhs)),
+            (Value::DateTime(lhs), Value::DateTime(rhs)) => Ok(Value::Duration(lhs - *rhs)),
+            (lhs, rhs) => Err(Error::incompatible_types(Arithmetic::Sub, &lhs, rhs)),
         }
     }
 }
@@ -179,9 +594,11 @@ impl Mul<&Self> for Value {
 
     fn mul(self, other: &Self) -> Self::Output {
         match (self, other) {
-            (Value::Numeric(lhs), Value::Numeric(rhs)) => Ok(Value::Numeric(lhs * rhs)),
-            (Value::Duration(lhs), Value::Numeric(rhs)) => Ok(Value::Duration(lhs * rhs.round() as u32)),
-            _ => bail!("Incompatible types for multiiplication"),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (lhs, rhs) if lhs.is_number() && rhs.is_number() => calculate_numbers(&lhs, rhs, Arithmetic::Mul),
+            (Value::Duration(lhs), rhs) if rhs.is_number() => scale_duration(&lhs, rhs, Arithmetic::Mul),
+            (lhs, Value::Duration(rhs)) if lhs.is_number() => scale_duration(rhs, &lhs, Arithmetic::Mul),
+            (lhs, rhs) => Err(Error::incompatible_types(Arithmetic::Mul, &lhs, rhs)),
         }
     }
 }
@@ -191,10 +608,265 @@ impl Div<&Self> for Value {
 
     fn div(self, other: &Self) -> Self::Output {
         match (self, other) {
-            (Value::Numeric(lhs), Value::Numeric(rhs)) => Ok(Value::Numeric(lhs / rhs)),
-            (Value::Duration(lhs), Value::Numeric(rhs)) => Ok(Value::Duration(lhs / rhs.round() as u32)),
-            _ => bail!("Incompatible types for division"),
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (lhs, rhs) if lhs.is_number() && rhs.is_number() => calculate_numbers(&lhs, rhs, Arithmetic::Div),
+            (Value::Duration(lhs), rhs) if rhs.is_number() => scale_duration(&lhs, rhs, Arithmetic::Div),
+            (lhs, rhs) => Err(Error::incompatible_types(Arithmetic::Div, &lhs, rhs)),
+        }
+    }
+}
+
+impl Rem<&Self> for Value {
+    type Output = Result<Value>;
+
+    /// The remainder of the euclidean division, so `-7 % 3 == 2`. The remainder of a [Value::Time] is the remainder
+    /// of the time since midnight (`now % "15min" == "0s"` every quarter of an hour).
+    fn rem(self, other: &Self) -> Self::Output {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (lhs, rhs) if lhs.is_number() && rhs.is_number() => calculate_numbers(&lhs, rhs, Arithmetic::Rem),
+            (Value::Duration(lhs), Value::Duration(rhs)) => divide_durations(&lhs, rhs, Arithmetic::Rem),
+            (Value::Time(lhs), Value::Duration(rhs)) => divide_durations(&since_midnight(&lhs), rhs, Arithmetic::Rem),
+            (lhs, rhs) => Err(Error::incompatible_types(Arithmetic::Rem, &lhs, rhs)),
+        }
+    }
+}
+
+impl Value {
+    /// `self` to the power of `other`. An integer to the power of a non-negative integer is an exact integer,
+    /// anything else is calculated as [f64].
+    pub fn pow(self, other: &Self) -> Result<Value> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (lhs, rhs) if lhs.is_number() && rhs.is_number() => calculate_numbers(&lhs, rhs, Arithmetic::Pow),
+            (lhs, rhs) => Err(Error::incompatible_types(Arithmetic::Pow, &lhs, rhs)),
+        }
+    }
+
+    /// The euclidean integer division of `self` by `other`, so `-7 // 3 == -3`. A [Value::Duration] divided by
+    /// another one is the [Value::Integer] number of times it fits in, a [Value::Time] is divided by the time since
+    /// midnight (`now // "15min"` is the number of the current quarter of an hour of the day).
+    pub fn div_euclid(self, other: &Self) -> Result<Value> {
+        match (self, other) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+            (lhs, rhs) if lhs.is_number() && rhs.is_number() => calculate_numbers(&lhs, rhs, Arithmetic::IntDiv),
+            (Value::Duration(lhs), Value::Duration(rhs)) => divide_durations(&lhs, rhs, Arithmetic::IntDiv),
+            (Value::Time(lhs), Value::Duration(rhs)) => {
+                divide_durations(&since_midnight(&lhs), rhs, Arithmetic::IntDiv)
+            }
+            (lhs, rhs) => Err(Error::incompatible_types(Arithmetic::IntDiv, &lhs, rhs)),
+        }
+    }
+}
+
+impl Neg for Value {
+    type Output = Result<Value>;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Value::Numeric(value) => Ok(Value::Numeric(-value)),
+            Value::Integer(value) => Value::from_integer(-(value as i128)),
+            Value::Unsigned(value) => Value::from_integer(-(value as i128)),
+            Value::Duration(value) => Ok(Value::Duration(-value)),
+            Value::Null => Ok(Value::Null),
+            value => Err(Error::IncompatibleType {
+                op: "-".into(),
+                value: value.type_name(),
+            }),
+        }
+    }
+}
+
+/// Apply `arithmetic` on two numbers. Integers are calculated exactly with checks for overflows, everything else
+/// as [f64]. A division of integers is only an integer if there is no remainder.
+fn calculate_numbers(lhs: &Value, rhs: &Value, arithmetic: Arithmetic) -> Result<Value> {
+    if let (Some(lhs), Some(rhs)) = (lhs.as_integer(), rhs.as_integer()) {
+        let result = match arithmetic {
+            Arithmetic::Add => lhs.checked_add(rhs),
+            Arithmetic::Sub => lhs.checked_sub(rhs),
+            Arithmetic::Mul => lhs.checked_mul(rhs),
+            Arithmetic::Div if rhs == 0 => return Err(Error::DivisionByZero),
+            Arithmetic::Div if lhs % rhs != 0 => return Ok(Value::Numeric(lhs as f64 / rhs as f64)),
+            Arithmetic::Div => lhs.checked_div(rhs),
+            Arithmetic::Rem | Arithmetic::IntDiv if rhs == 0 => return Err(Error::DivisionByZero),
+            Arithmetic::Rem => lhs.checked_rem_euclid(rhs),
+            Arithmetic::IntDiv => lhs.checked_div_euclid(rhs),
+            // Negative exponents give fractions, which divide by zero for a zero base
+            Arithmetic::Pow if rhs < 0 && lhs == 0 => return Err(Error::DivisionByZero),
+            Arithmetic::Pow if rhs < 0 => return Ok(Value::Numeric((lhs as f64).powf(rhs as f64))),
+            // These bases never overflow, however large the exponent is
+            Arithmetic::Pow if lhs == 0 || lhs == 1 => Some(if rhs == 0 { 1 } else { lhs }),
+            Arithmetic::Pow if lhs == -1 => Some(if rhs % 2 == 0 { 1 } else { -1 }),
+            Arithmetic::Pow => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_pow(rhs)),
+        };
+        return result
+            .ok_or_else(|| Error::OutOfRange(format!("{} {} {} overflows", lhs, arithmetic, rhs)))
+            .and_then(Value::from_integer);
+    }
+
+    // Both are numbers, so both have a float representation
+    let (lhs, rhs) = (lhs.as_float().unwrap_or_default(), rhs.as_float().unwrap_or_default());
+    match arithmetic {
+        Arithmetic::Add => Ok(Value::Numeric(lhs + rhs)),
+        Arithmetic::Sub => Ok(Value::Numeric(lhs - rhs)),
+        Arithmetic::Mul => Ok(Value::Numeric(lhs * rhs)),
+        Arithmetic::Div if rhs == 0f64 => Err(Error::DivisionByZero),
+        Arithmetic::Div => Ok(Value::Numeric(lhs / rhs)),
+        Arithmetic::Rem | Arithmetic::IntDiv if rhs == 0f64 => Err(Error::DivisionByZero),
+        Arithmetic::Rem => Ok(Value::Numeric(lhs.rem_euclid(rhs))),
+        Arithmetic::IntDiv => Ok(Value::Numeric(lhs.div_euclid(rhs))),
+        Arithmetic::Pow if lhs == 0f64 && rhs < 0f64 => Err(Error::DivisionByZero),
+        Arithmetic::Pow => match lhs.powf(rhs) {
+            // Roots of negative numbers are not real and overflows are infinite
+            result if !result.is_finite() => Err(Error::OutOfRange(format!(
+                "{} {} {} is not a finite number",
+                lhs,
+                Arithmetic::Pow,
+                rhs
+            ))),
+            result => Ok(Value::Numeric(result)),
+        },
+    }
+}
+
+/// The euclidean remainder ([Arithmetic::Rem]) of `lhs` divided by `rhs` as [Value::Duration], or the number of times
+/// `rhs` fits into `lhs` ([Arithmetic::IntDiv]) as [Value::Integer]
+fn divide_durations(lhs: &Duration, rhs: &Duration, arithmetic: Arithmetic) -> Result<Value> {
+    let (lhs, rhs) = (duration_nanoseconds(lhs), duration_nanoseconds(rhs));
+    if rhs == 0 {
+        return Err(Error::DivisionByZero);
+    }
+    match arithmetic {
+        // The remainder is less than `rhs`, so it always is a valid duration
+        Arithmetic::Rem => Ok(Value::Duration(
+            duration_from_nanoseconds(lhs.rem_euclid(rhs)).unwrap_or_else(Duration::zero),
+        )),
+        _ => Value::from_integer(lhs.div_euclid(rhs)),
+    }
+}
+
+/// The [Duration] from midnight until `time`
+fn since_midnight(time: &NaiveTime) -> Duration {
+    *time - NaiveTime::from_hms(0, 0, 0)
+}
+
+/// Multiply or divide `duration` by the number `factor`. Integers are calculated exactly on nanoseconds, anything
+/// else is rounded to the nearest nanosecond.
+fn scale_duration(duration: &Duration, factor: &Value, arithmetic: Arithmetic) -> Result<Value> {
+    let nanoseconds = duration_nanoseconds(duration);
+    let result = match (factor.as_integer(), arithmetic) {
+        (Some(0), Arithmetic::Div) => return Err(Error::DivisionByZero),
+        (Some(factor), Arithmetic::Mul) => nanoseconds.checked_mul(factor),
+        (Some(factor), Arithmetic::Div) => nanoseconds.checked_div(factor),
+        (None, _) => {
+            let factor = factor.as_float().unwrap_or_default();
+            let result = match arithmetic {
+                Arithmetic::Div if factor == 0f64 => return Err(Error::DivisionByZero),
+                Arithmetic::Div => nanoseconds as f64 / factor,
+                _ => nanoseconds as f64 * factor,
+            };
+            // NaN and infinity are out of range as well
+            (result.abs() <= i128::MAX as f64).then(|| result.round() as i128)
+        }
+        _ => None,
+    };
+    result
+        .and_then(duration_from_nanoseconds)
+        .map(Value::Duration)
+        .ok_or_else(|| out_of_range(arithmetic, &FormatDuration(duration), factor))
+}
+
+/// All nanoseconds of a [Duration], which do not fit into an [i64] for long durations
+fn duration_nanoseconds(duration: &Duration) -> i128 {
+    let seconds = duration.num_seconds();
+    let fraction = (*duration - Duration::seconds(seconds))
+        .num_nanoseconds()
+        .unwrap_or_default();
+    seconds as i128 * 1_000_000_000 + fraction as i128
+}
+
+/// The [Duration] of `nanoseconds` or [None] if it exceeds the range of a [Duration]
+fn duration_from_nanoseconds(nanoseconds: i128) -> Option<Duration> {
+    let max = duration_nanoseconds(&Duration::max_value());
+    if nanoseconds.abs() > max {
+        return None;
+    }
+    let seconds = (nanoseconds / 1_000_000_000) as i64;
+    let fraction = (nanoseconds % 1_000_000_000) as i64;
+    Some(Duration::seconds(seconds) + Duration::nanoseconds(fraction))
+}
+
+/// Writes a string to be read back as string literal: quotes and control characters are escaped, backslashes only
+/// if they would start an escape sequence, so regular expressions stay readable (`"^v1\.\d+"`)
+struct EscapeString<'a>(&'a str);
+
+impl Display for EscapeString<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut chars = self.0.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' if matches!(
+                    chars.peek(),
+                    None | Some('"' | '\'' | '\\' | 'n' | 'r' | 't' | '0' | 'u')
+                ) =>
+                {
+                    write!(f, "\\\\")?
+                }
+                '"' => write!(f, "\\\"")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '\t' => write!(f, "\\t")?,
+                '\0' => write!(f, "\\0")?,
+                c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32)?,
+                c => write!(f, "{}", c)?,
+            }
         }
+        Ok(())
+    }
+}
+
+/// Writes a [Duration] in [humantime] format with a leading `-` if it is negative
+struct FormatDuration<'a>(&'a Duration);
+
+impl Display for FormatDuration<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (sign, duration) = if *self.0 < Duration::zero() {
+            ("-", -*self.0)
+        } else {
+            ("", *self.0)
+        };
+        // A non-negative chrono::Duration always fits into a std::time::Duration
+        let duration = duration.to_std().unwrap_or_default();
+        write!(f, "{}{}", sign, humantime::format_duration(duration))
+    }
+}
+
+/// [Value::Duration] is serialized as seconds and nanoseconds like a [std::time::Duration], both with the sign of
+/// the [Duration]
+#[cfg(feature = "serde_de")]
+mod serde_duration {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Parts {
+        secs: i64,
+        nanos: i32,
+    }
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        let secs = duration.num_seconds();
+        let nanos = (*duration - Duration::seconds(secs))
+            .num_nanoseconds()
+            .unwrap_or_default() as i32;
+        Parts { secs, nanos }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let Parts { secs, nanos } = Parts::deserialize(deserializer)?;
+        Duration::seconds(secs)
+            .checked_add(&Duration::nanoseconds(nanos.into()))
+            .ok_or_else(|| serde::de::Error::custom("duration out of range"))
     }
 }
 
@@ -208,12 +880,36 @@ macro_rules! impl_value {
     };
 }
 
-/// One lazy helper
-impl TryFrom<chrono::Duration> for Value {
-    type Error = anyhow::Error;
+fn out_of_range(arithmetic: Arithmetic, lhs: &impl Display, rhs: &impl Display) -> Error {
+    Error::OutOfRange(format!("{} {} {} exceeds the supported range", lhs, arithmetic, rhs))
+}
+
+/// A [Vec] is a [Value::List]
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+/// [None] is [Value::Null]
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+/// A [std::time::Duration] beyond the range of a [Duration] (about 292 million years) saturates at
+/// [Duration::max_value]; see [Value::try_from_std] for a checked conversion
+impl From<std::time::Duration> for Value {
+    fn from(duration: std::time::Duration) -> Self {
+        Value::Duration(Duration::from_std(duration).unwrap_or_else(|_| Duration::max_value()))
+    }
+}
 
-    fn try_from(duration: chrono::Duration) -> Result<Self, Self::Error> {
-        Ok(duration.to_std()?.into())
+/// A [u64] is a [Value::Integer] up to [i64::MAX] and a [Value::Unsigned] above
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(Value::Unsigned(value), Value::Integer)
     }
 }
 
@@ -225,8 +921,12 @@ impl_value!(i16, Value::Numeric);
 impl_value!(u16, Value::Numeric);
 impl_value!(i32, Value::Numeric);
 impl_value!(u32, Value::Numeric);
+impl_value!(i64, Value::Integer);
 impl_value!(f32, Value::Numeric);
 impl_value!(f64, Value::Numeric);
 impl_value!(bool, Value::Bool);
 impl_value!(NaiveTime, Value::Time);
+impl_value!(NaiveDate, Value::Date);
+impl_value!(DateTime<FixedOffset>, Value::DateTime);
+impl_value!(DateTime<Utc>, Value::DateTime);
 impl_value!(Duration, Value::Duration);